@@ -0,0 +1,197 @@
+//! Dodge the falling rocks: a second game on the snake engine.
+//!
+//! Run it with `cargo run --example dodge`. Move with the left and right arrows, `Esc` quits.
+
+use rand::Rng;
+use snake::game_engine::{
+    self, Action, BoxedObject, Draw, DrawLayer, Game, GameData, GameEvent, GameObject, GameScene,
+    GameScreen, ObjectId, Update, World,
+};
+use snake::game_utils::{Direction, Position, Speed};
+use std::any::Any;
+use std::time::Duration;
+
+const ROCKS: usize = 8;
+
+/// Rocks come and go as objects of their own, so the scene has nothing to tell them.
+#[derive(Copy, Clone, Debug)]
+enum DodgeActions {}
+
+impl GameEvent for DodgeActions {}
+
+struct Player {
+    position: Position,
+}
+
+impl Draw for Player {
+    fn draw(&self, screen: &mut GameScreen) {
+        let (x, y) = self.position.get_screen_coordinates();
+        Game::<DodgeActions>::draw_point(screen, "@", x, y);
+    }
+    fn get_position(&self) -> Position {
+        self.position
+    }
+}
+
+impl Update for Player {
+    fn update(&mut self, _time_since_last_call: Duration) {}
+}
+
+impl GameObject for Player {
+    type Item = DodgeActions;
+
+    fn action(&mut self, action: Action) {
+        let direction = match action {
+            Action::MoveLeft => Direction::left(),
+            Action::MoveRight => Direction::right(),
+            _ => return,
+        };
+        let position = self.position + direction * 1f64;
+        if (1..=game_engine::GAME_AREA_WIDTH).contains(&position.get_screen_coordinates().0) {
+            self.position = position;
+        }
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+struct Rock {
+    position: Position,
+    speed: Speed,
+    /// Set when the rock reached the ground without hitting the player.
+    landed: bool,
+}
+
+impl Rock {
+    /// A rock at a random column, `height` cells above the board.
+    fn spawn(height: usize) -> BoxedObject<DodgeActions> {
+        let mut rng = rand::thread_rng();
+        let rock = Rock {
+            position: Position {
+                x: rng.gen_range(1..=game_engine::GAME_AREA_WIDTH) as f64,
+                y: -(height as f64),
+            },
+            speed: Speed::period_in_milliseconds(rng.gen_range(80..300)),
+            landed: false,
+        };
+        Box::new(rock)
+    }
+}
+
+impl Draw for Rock {
+    fn draw(&self, screen: &mut GameScreen) {
+        let (x, y) = self.position.get_screen_coordinates();
+        if self.position.y >= 1.0 {
+            Game::<DodgeActions>::draw_point(screen, "*", x, y);
+        }
+    }
+    fn get_position(&self) -> Position {
+        self.position
+    }
+    fn layer(&self) -> DrawLayer {
+        DrawLayer::Items
+    }
+}
+
+impl Update for Rock {
+    fn update(&mut self, time_since_last_call: Duration) {
+        let steps =
+            self.speed.get_speed_steps_per_millisecond() * time_since_last_call.as_millis() as f64;
+        self.position = self.position + Direction::down() * steps;
+        self.landed = self.position.get_screen_coordinates().1 > game_engine::GAME_AREA_HEIGHT;
+    }
+}
+
+impl GameObject for Rock {
+    type Item = DodgeActions;
+
+    fn action(&mut self, _action: Action) {}
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+struct DodgeScene {
+    /// Set once the scene is loaded.
+    player: Option<ObjectId>,
+    rocks: Vec<ObjectId>,
+    dodged: u32,
+    hits: u32,
+}
+
+impl GameScene<DodgeActions> for DodgeScene {
+    fn load(&mut self, world: &mut World<DodgeActions>) {
+        let player = Player {
+            position: Position {
+                x: (game_engine::GAME_AREA_WIDTH / 2) as f64,
+                y: game_engine::GAME_AREA_HEIGHT as f64,
+            },
+        };
+        self.player = Some(world.add_object(Box::new(player)));
+        for _ in 0..ROCKS {
+            let rock = Rock::spawn(rand::thread_rng().gen_range(0..game_engine::GAME_AREA_HEIGHT));
+            self.rocks.push(world.add_object(rock));
+        }
+    }
+    /// Takes out the rocks that hit the player or reached the ground and drops new ones.
+    fn update(&mut self, world: &mut World<DodgeActions>, _interval: Duration) {
+        let player = match self.player.and_then(|player| world.get::<Player>(player)) {
+            Some(player) => player.position,
+            None => return,
+        };
+        for id in &mut self.rocks {
+            let (hit, landed) = match world.get::<Rock>(*id) {
+                Some(rock) => (rock.position == player, rock.landed),
+                None => continue,
+            };
+            if hit {
+                self.hits += 1;
+            } else if landed {
+                self.dodged += 1;
+            } else {
+                continue;
+            }
+            world.remove_object(*id);
+            *id = world.add_object(Rock::spawn(0));
+        }
+    }
+    fn draw_hud(&self, _world: &World<DodgeActions>, _width: usize, _height: usize) -> Vec<String> {
+        vec![
+            format!("Dodged :{:>6}", self.dodged),
+            format!("Hits   :{:>6}", self.hits),
+            String::new(),
+            String::from("Left/Right arrows - Move  /  Esc - Quit"),
+        ]
+    }
+    fn draw_title(&self, width: usize, _height: usize) -> String {
+        format!("{:^width$}", "*** DODGE ***", width = width)
+    }
+    fn input(&mut self, world: &mut World<DodgeActions>, game_data: GameData) {
+        if let Some(player) = self.player.and_then(|player| world.object_mut(player)) {
+            player.action(game_data.action);
+        }
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+fn main() -> Result<(), String> {
+    let scene = DodgeScene {
+        player: None,
+        rocks: Vec::new(),
+        dodged: 0,
+        hits: 0,
+    };
+    Game::run(Box::new(scene), |_| {})
+}
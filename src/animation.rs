@@ -0,0 +1,294 @@
+use crate::ecs::{Components, Entity, Position};
+use crate::game_engine::DrawLayer;
+use std::time::Duration;
+
+/// The time an animation or a tween follows.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Clock {
+    /// Game time: it moves on with the ticks, stands still while the scene is paused and
+    /// plays out the same way in replays and headless runs.
+    #[default]
+    Simulation,
+    /// Time between the frames drawn, which goes on while the scene is paused. Nothing
+    /// moves it when the scene runs without being drawn.
+    Render,
+}
+
+/// How a tween moves from its start to its end over time.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Starts slow and speeds up.
+    EaseIn,
+    /// Starts fast and slows down.
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// How far along the way it is when `t`, from 0 to 1, of the time has passed.
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut if t < 0.5 => 2.0 * t * t,
+            Easing::EaseInOut => 1.0 - 2.0 * (1.0 - t) * (1.0 - t),
+        }
+    }
+}
+
+/// A value going from `from` to `to` over `duration`. Attached to an entity, the engine
+/// moves it on with its clock for the game to read.
+#[derive(Copy, Clone, Debug)]
+pub struct Tween {
+    pub from: f64,
+    pub to: f64,
+    duration: Duration,
+    easing: Easing,
+    clock: Clock,
+    elapsed: Duration,
+}
+
+impl Tween {
+    pub fn new(from: f64, to: f64, duration: Duration) -> Tween {
+        Tween {
+            from,
+            to,
+            duration,
+            easing: Easing::default(),
+            clock: Clock::default(),
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn easing(self, easing: Easing) -> Tween {
+        Tween { easing, ..self }
+    }
+
+    /// Makes the tween follow `clock` instead of game time.
+    pub fn on(self, clock: Clock) -> Tween {
+        Tween { clock, ..self }
+    }
+
+    pub fn value(&self) -> f64 {
+        let t = match self.duration.is_zero() {
+            true => 1.0,
+            false => self.elapsed.as_secs_f64() / self.duration.as_secs_f64(),
+        };
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Moves the tween on by `interval`. Only the engine calls it for the tweens of
+    /// components, a game calls it for the tweens it keeps itself.
+    pub fn advance(&mut self, interval: Duration) {
+        self.elapsed = (self.elapsed + interval).min(self.duration);
+    }
+}
+
+/// Moves an entity's `Position` from one place to another along a tween, then takes itself
+/// off the entity.
+#[derive(Copy, Clone, Debug)]
+pub struct Motion {
+    from: Position,
+    to: Position,
+    tween: Tween,
+}
+
+impl Motion {
+    /// Goes from `from` to `to` as `tween` goes from 0 to 1, whatever its own ends are.
+    pub fn new(from: Position, to: Position, tween: Tween) -> Motion {
+        Motion {
+            from,
+            to,
+            tween: Tween {
+                from: 0.0,
+                to: 1.0,
+                ..tween
+            },
+        }
+    }
+
+    fn position(&self) -> Position {
+        let t = self.tween.value();
+        Position {
+            x: self.from.x + (self.to.x - self.from.x) * t,
+            y: self.from.y + (self.to.y - self.from.y) * t,
+        }
+    }
+}
+
+/// Text drawn centered on an entity's `Position`, in the theme's style for `style`, such as
+/// a countdown or points earned. Unlike a `Glyph` it isn't bound to a cell.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Label {
+    pub text: String,
+    pub style: &'static str,
+    pub layer: DrawLayer,
+}
+
+impl Label {
+    pub fn new(text: impl Into<String>, style: &'static str, layer: DrawLayer) -> Label {
+        Label {
+            text: text.into(),
+            style,
+            layer,
+        }
+    }
+}
+
+/// What an animated entity looks like in a frame of its `Animation`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Frame {
+    /// The entity as its `Glyph` or `Label` draws it.
+    Shown,
+    /// Nothing.
+    Hidden,
+    /// The glyph set's character for a part, in place of the `Glyph`'s.
+    Glyph(&'static str),
+    /// Text in place of the `Label`'s.
+    Text(&'static str),
+}
+
+/// Frames an entity goes through, each shown for `frame_time`. Once over, the animation
+/// takes itself off the entity, or despawns it if it was made with `then_despawn`.
+#[derive(Clone, Debug)]
+pub struct Animation {
+    frames: Vec<Frame>,
+    frame_time: Duration,
+    /// Times the frames are played, or `None` to play them until the animation is removed.
+    repeats: Option<u32>,
+    /// Time the entity is shown as it is before the first frame.
+    delay: Duration,
+    despawn: bool,
+    clock: Clock,
+    elapsed: Duration,
+}
+
+impl Animation {
+    /// Plays `frames` once.
+    ///
+    /// Panics if `frame_time` is zero or there are no frames.
+    pub fn new(frames: Vec<Frame>, frame_time: Duration) -> Animation {
+        assert!(!frames.is_empty(), "An animation needs frames");
+        assert!(!frame_time.is_zero(), "An animation needs a frame time");
+        Animation {
+            frames,
+            frame_time,
+            repeats: Some(1),
+            delay: Duration::ZERO,
+            despawn: false,
+            clock: Clock::default(),
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Hides and shows the entity `times` times, every `period`.
+    pub fn blink(period: Duration, times: u32) -> Animation {
+        Animation::new(vec![Frame::Hidden, Frame::Shown], period / 2).repeat(times)
+    }
+
+    pub fn repeat(self, times: u32) -> Animation {
+        Animation {
+            repeats: Some(times),
+            ..self
+        }
+    }
+
+    /// Plays the frames over and over until the animation is removed.
+    pub fn looped(self) -> Animation {
+        Animation {
+            repeats: None,
+            ..self
+        }
+    }
+
+    /// Waits `delay` before the first frame.
+    pub fn delayed(self, delay: Duration) -> Animation {
+        Animation { delay, ..self }
+    }
+
+    /// Despawns the entity once the animation is over.
+    pub fn then_despawn(self) -> Animation {
+        Animation {
+            despawn: true,
+            ..self
+        }
+    }
+
+    /// Makes the animation follow `clock` instead of game time.
+    pub fn on(self, clock: Clock) -> Animation {
+        Animation { clock, ..self }
+    }
+
+    /// Time the animation takes from start to end, or `None` if it loops.
+    pub fn duration(&self) -> Option<Duration> {
+        let frames = self.frame_time * self.frames.len() as u32;
+        Some(self.delay + frames * self.repeats?)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.duration()
+            .is_some_and(|duration| self.elapsed >= duration)
+    }
+
+    /// The frame to draw now.
+    pub fn frame(&self) -> Frame {
+        if self.elapsed < self.delay || self.finished() {
+            return Frame::Shown;
+        }
+        let frame = (self.elapsed - self.delay).as_nanos() / self.frame_time.as_nanos();
+        self.frames[frame as usize % self.frames.len()]
+    }
+}
+
+/// Moves the animations, tweens and motions that follow `clock` on by `interval`. Returns
+/// the entities whose animation is over and asked to be despawned.
+pub(crate) fn advance(
+    components: &mut Components,
+    clock: Clock,
+    interval: Duration,
+) -> Vec<Entity> {
+    let mut finished = Vec::new();
+    let mut despawned = Vec::new();
+    for (entity, animation) in components.iter_mut::<Animation>() {
+        if animation.clock != clock {
+            continue;
+        }
+        animation.elapsed += interval;
+        if animation.finished() {
+            match animation.despawn {
+                true => despawned.push(entity),
+                false => finished.push(entity),
+            }
+        }
+    }
+    for (_, tween) in components.iter_mut::<Tween>() {
+        if tween.clock == clock {
+            tween.advance(interval);
+        }
+    }
+    let mut moved = Vec::new();
+    components.join_mut(|entity, motion: &mut Motion, position: &mut Position| {
+        if motion.tween.clock == clock {
+            motion.tween.advance(interval);
+            *position = motion.position();
+            if motion.tween.finished() {
+                moved.push(entity);
+            }
+        }
+    });
+    for entity in finished {
+        components.remove::<Animation>(entity);
+    }
+    for entity in moved {
+        components.remove::<Motion>(entity);
+    }
+    despawned
+}
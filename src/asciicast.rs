@@ -0,0 +1,84 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Writes terminal output as an asciicast v2 file, playable with `asciinema play`.
+///
+/// Output is buffered with `capture` and becomes one event on `frame`. Timestamps come from
+/// the wall clock, or from `set_time` once the writer is switched to simulated time for
+/// rendering without a terminal.
+pub struct AsciicastWriter {
+    file: BufWriter<File>,
+    frame: Vec<u8>,
+    start: Instant,
+    simulated_time: Option<Duration>,
+}
+
+impl AsciicastWriter {
+    pub fn create(path: &str, width: usize, height: usize) -> std::io::Result<AsciicastWriter> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        writeln!(
+            file,
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}, \"env\": {{\"TERM\": \"xterm-256color\"}}}}",
+            width, height, timestamp
+        )?;
+        Ok(AsciicastWriter {
+            file,
+            frame: Vec::new(),
+            start: Instant::now(),
+            simulated_time: None,
+        })
+    }
+
+    /// Stamps the following frames with `time` instead of the wall clock.
+    pub fn set_time(&mut self, time: Duration) {
+        self.simulated_time = Some(time);
+    }
+
+    pub fn capture(&mut self, data: &[u8]) {
+        self.frame.extend_from_slice(data);
+    }
+
+    /// Writes everything captured since the last frame as a single output event.
+    pub fn frame(&mut self) -> std::io::Result<()> {
+        if self.frame.is_empty() {
+            return Ok(());
+        }
+        let time = self
+            .simulated_time
+            .unwrap_or_else(|| self.start.elapsed())
+            .as_secs_f64();
+        let data = String::from_utf8_lossy(&self.frame);
+        writeln!(self.file, "[{:.6}, \"o\", {}]", time, json_string(&data))?;
+        self.frame.clear();
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> std::io::Result<()> {
+        self.frame()?;
+        self.file.flush()
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => {
+                json.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
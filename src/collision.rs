@@ -0,0 +1,205 @@
+use crate::game_engine::ObjectId;
+use std::collections::HashMap;
+
+/// A board cell: column and row, numbered from 1.
+pub type Cell = (usize, usize);
+
+/// The cells an object takes, as far as collisions go.
+#[derive(Clone, Debug)]
+pub struct Collider {
+    /// Layer the object is in. Objects only collide if the scene paired their layers.
+    pub layer: &'static str,
+    /// Every cell the object takes. The first one of a moving object is its head.
+    pub cells: Vec<Cell>,
+    /// Whether the object runs into others with its head. Objects that don't move only get
+    /// run into.
+    pub moving: bool,
+}
+
+/// The head of `mover` is on a cell `other` takes. `other` is `mover` itself when the head
+/// ran into the rest of its body.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Collision {
+    pub mover: ObjectId,
+    pub mover_layer: &'static str,
+    pub other: ObjectId,
+    pub other_layer: &'static str,
+    pub cell: Cell,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Owner {
+    id: ObjectId,
+    layer: &'static str,
+    /// Position of the cell among the object's cells; 0 is the head of a moving object.
+    index: usize,
+}
+
+/// Which objects take each cell, and the collisions between them, as of the last time it
+/// was built.
+#[derive(Clone, Debug, Default)]
+pub struct CollisionIndex {
+    rules: Vec<(&'static str, &'static str)>,
+    owners: HashMap<Cell, Vec<Owner>>,
+    collisions: Vec<Collision>,
+}
+
+impl CollisionIndex {
+    /// Makes heads in layer `a` collide with cells in layer `b`, and the other way round.
+    pub fn add_rule(&mut self, a: &'static str, b: &'static str) {
+        if !self.collides(a, b) {
+            self.rules.push((a, b));
+        }
+    }
+
+    pub fn collides(&self, a: &str, b: &str) -> bool {
+        self.rules
+            .iter()
+            .any(|&(x, y)| (x, y) == (a, b) || (x, y) == (b, a))
+    }
+
+    /// Indexes the cells of `colliders` and finds every head that is on a cell of an
+    /// object in a layer its own is paired with.
+    pub fn build(&mut self, colliders: impl IntoIterator<Item = (ObjectId, Collider)>) {
+        self.owners.clear();
+        self.collisions.clear();
+        let mut heads = Vec::new();
+        for (id, collider) in colliders {
+            for (index, cell) in collider.cells.iter().enumerate() {
+                let owner = Owner {
+                    id,
+                    layer: collider.layer,
+                    index,
+                };
+                self.owners.entry(*cell).or_default().push(owner);
+            }
+            if let (true, Some(head)) = (collider.moving, collider.cells.first()) {
+                heads.push((id, collider.layer, *head));
+            }
+        }
+        for (mover, mover_layer, cell) in heads {
+            for owner in &self.owners[&cell] {
+                if (owner.id == mover && owner.index == 0)
+                    || !self.collides(mover_layer, owner.layer)
+                {
+                    continue;
+                }
+                self.collisions.push(Collision {
+                    mover,
+                    mover_layer,
+                    other: owner.id,
+                    other_layer: owner.layer,
+                    cell,
+                });
+            }
+        }
+    }
+
+    /// Collisions found the last time the index was built, in the order objects were added.
+    pub fn collisions(&self) -> &[Collision] {
+        &self.collisions
+    }
+
+    /// Objects that take `cell`.
+    pub fn objects_at(&self, cell: Cell) -> Vec<ObjectId> {
+        let mut ids: Vec<_> = self.owners.get(&cell).map_or_else(Vec::new, |owners| {
+            owners.iter().map(|owner| owner.id).collect()
+        });
+        ids.dedup();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collider(layer: &'static str, cells: &[Cell], moving: bool) -> Collider {
+        Collider {
+            layer,
+            cells: cells.to_vec(),
+            moving,
+        }
+    }
+
+    fn collision(
+        mover: u64,
+        mover_layer: &'static str,
+        other: u64,
+        other_layer: &'static str,
+    ) -> Collision {
+        Collision {
+            mover: ObjectId(mover),
+            mover_layer,
+            other: ObjectId(other),
+            other_layer,
+            cell: (2, 2),
+        }
+    }
+
+    #[test]
+    fn heads_collide_with_paired_layers_only() {
+        let mut index = CollisionIndex::default();
+        index.add_rule("snake", "food");
+        index.build([
+            (ObjectId(1), collider("snake", &[(2, 2), (1, 2)], true)),
+            (ObjectId(2), collider("walls", &[(2, 2)], false)),
+            (ObjectId(3), collider("food", &[(2, 2)], false)),
+        ]);
+        assert_eq!(index.collisions(), [collision(1, "snake", 3, "food")]);
+    }
+
+    #[test]
+    fn rules_go_both_ways() {
+        let mut index = CollisionIndex::default();
+        index.add_rule("snake", "hazard");
+        index.add_rule("hazard", "snake");
+        assert!(index.collides("hazard", "snake"));
+        assert!(!index.collides("snake", "snake"));
+        index.build([
+            (ObjectId(1), collider("snake", &[(3, 2), (2, 2)], true)),
+            (ObjectId(2), collider("hazard", &[(2, 2)], true)),
+        ]);
+        assert_eq!(index.collisions(), [collision(2, "hazard", 1, "snake")]);
+    }
+
+    #[test]
+    fn a_head_runs_into_its_own_body() {
+        let mut index = CollisionIndex::default();
+        let snake = collider("snake", &[(2, 2), (2, 3), (3, 3), (3, 2), (2, 2)], true);
+        index.build([(ObjectId(1), snake.clone())]);
+        assert!(index.collisions().is_empty());
+        index.add_rule("snake", "snake");
+        index.build([(ObjectId(1), snake)]);
+        assert_eq!(index.collisions(), [collision(1, "snake", 1, "snake")]);
+    }
+
+    #[test]
+    fn objects_that_dont_move_only_get_run_into() {
+        let mut index = CollisionIndex::default();
+        index.add_rule("walls", "food");
+        index.build([
+            (ObjectId(1), collider("walls", &[(2, 2)], false)),
+            (ObjectId(2), collider("food", &[(2, 2)], false)),
+        ]);
+        assert!(index.collisions().is_empty());
+        assert_eq!(index.objects_at((2, 2)), [ObjectId(1), ObjectId(2)]);
+        assert!(index.objects_at((5, 5)).is_empty());
+    }
+
+    #[test]
+    fn building_again_forgets_the_last_cells() {
+        let mut index = CollisionIndex::default();
+        index.add_rule("snake", "food");
+        index.build([
+            (ObjectId(1), collider("snake", &[(2, 2)], true)),
+            (ObjectId(2), collider("food", &[(2, 2)], false)),
+        ]);
+        index.build([
+            (ObjectId(1), collider("snake", &[(3, 2), (2, 2)], true)),
+            (ObjectId(2), collider("food", &[(8, 8)], false)),
+        ]);
+        assert!(index.collisions().is_empty());
+        assert_eq!(index.objects_at((2, 2)), [ObjectId(1)]);
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Frames whose draw time the percentiles are taken over.
+const FRAME_SAMPLES: usize = 120;
+/// Time the rates are counted over before they are shown.
+const PERIOD: Duration = Duration::from_secs(1);
+
+/// What the output thread tells the game thread about the frames it writes, for the
+/// overlay. Counters are all that is shared between them besides the frames themselves.
+#[derive(Default, Debug)]
+pub(crate) struct OutputStats {
+    /// Frames sent to the output thread that it hasn't written yet.
+    queued: AtomicUsize,
+    /// Longest time a frame took to write to the terminal since the game thread last
+    /// looked, in microseconds.
+    write_time: AtomicU64,
+}
+
+impl OutputStats {
+    pub fn sent(&self) {
+        self.queued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn written(&self, time: Duration) {
+        self.queued.fetch_sub(1, Ordering::Relaxed);
+        self.write_time
+            .fetch_max(time.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Counts what the game thread does and measures it over the last whole second: the frames
+/// it draws and how long they take, the ticks it runs or drops, the bytes it sends to the
+/// terminal and how long it waits for input. `lines` shows it all.
+pub(crate) struct DebugStats {
+    output: Arc<OutputStats>,
+    period_start: Instant,
+    /// Counted since `period_start`.
+    current: Counts,
+    /// Counted over the last whole period.
+    last: Counts,
+    /// Share of the last period the game thread wasn't waiting for input.
+    busy: f64,
+    write_time: Duration,
+    frame_times: VecDeque<Duration>,
+}
+
+#[derive(Copy, Clone, Default, Debug)]
+struct Counts {
+    frames: usize,
+    ticks: usize,
+    bytes: usize,
+    waited: Duration,
+    /// Most ticks run at once to catch up with the clock.
+    catch_up: usize,
+    /// Ticks the game thread fell too far behind to run.
+    dropped: usize,
+    /// Most frames waiting for the output thread.
+    queued: usize,
+}
+
+impl DebugStats {
+    pub fn new(output: Arc<OutputStats>) -> DebugStats {
+        DebugStats {
+            output,
+            period_start: Instant::now(),
+            current: Counts::default(),
+            last: Counts::default(),
+            busy: 0.0,
+            write_time: Duration::ZERO,
+            frame_times: VecDeque::with_capacity(FRAME_SAMPLES),
+        }
+    }
+
+    /// Counts the ticks run in a pass of the game loop, and those dropped.
+    pub fn ticks(&mut self, ticks: usize, dropped: usize) {
+        self.current.ticks += ticks;
+        self.current.dropped += dropped;
+        self.current.catch_up = self.current.catch_up.max(ticks);
+        self.roll();
+    }
+
+    /// Counts a frame that took `time` to draw and was `bytes` long.
+    pub fn frame(&mut self, time: Duration, bytes: usize) {
+        self.current.frames += 1;
+        self.current.bytes += bytes;
+        let queued = self.output.queued.load(Ordering::Relaxed);
+        self.current.queued = self.current.queued.max(queued);
+        if self.frame_times.len() == FRAME_SAMPLES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(time);
+    }
+
+    /// Counts time the game thread spent waiting for input.
+    pub fn waited(&mut self, time: Duration) {
+        self.current.waited += time;
+    }
+
+    /// Frames drawn during the last whole second.
+    pub fn fps(&self) -> usize {
+        self.last.frames
+    }
+
+    /// The overlay's text, a line for each row.
+    pub fn lines(&self, objects: usize, entities: usize, tick: u64) -> Vec<String> {
+        let [p50, p95, p99] = [0.5, 0.95, 0.99].map(|p| self.frame_time(p));
+        vec![
+            format!(
+                "{} fps  {} ticks/s  catch-up {}  dropped {}  busy {:.0}%",
+                self.last.frames,
+                self.last.ticks,
+                self.last.catch_up,
+                self.last.dropped,
+                self.busy * 100.0
+            ),
+            format!(
+                "frame {} / {} / {} ms (p50/p95/p99)",
+                millis(p50),
+                millis(p95),
+                millis(p99)
+            ),
+            format!(
+                "out {:.1} KB/s  queued {}  write {} ms",
+                self.last.bytes as f64 / 1024.0,
+                self.last.queued,
+                millis(self.write_time)
+            ),
+            format!("objects {}  entities {}  tick {}", objects, entities, tick),
+        ]
+    }
+
+    /// Draw time `p` of the recent frames took at most.
+    fn frame_time(&self, p: f64) -> Duration {
+        let mut times: Vec<Duration> = self.frame_times.iter().copied().collect();
+        times.sort();
+        match times.len() {
+            0 => Duration::ZERO,
+            n => times[((n - 1) as f64 * p).round() as usize],
+        }
+    }
+
+    /// Starts a new period once the current one is over.
+    fn roll(&mut self) {
+        let elapsed = self.period_start.elapsed();
+        if elapsed < PERIOD {
+            return;
+        }
+        self.last = std::mem::take(&mut self.current);
+        self.busy = 1.0 - (self.last.waited.as_secs_f64() / elapsed.as_secs_f64()).min(1.0);
+        let write_time = self.output.write_time.swap(0, Ordering::Relaxed);
+        self.write_time = Duration::from_micros(write_time);
+        self.period_start = Instant::now();
+    }
+}
+
+fn millis(time: Duration) -> String {
+    format!("{:.1}", time.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,341 @@
+use crate::game_engine::{DrawLayer, ObjectId, World};
+use crate::game_utils::{Direction, Speed};
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Duration;
+
+pub use crate::collision::Collider;
+pub use crate::game_utils::Position;
+
+/// A thing in the game made only of the components attached to it. Entities are numbered
+/// with objects, so collisions and draw order treat both the same way.
+pub type Entity = ObjectId;
+
+/// Data attached to an entity. Any `Send` type can be one, and an entity has at most one
+/// component of each type.
+pub trait Component: Any + Send {}
+
+impl<T: Any + Send> Component for T {}
+
+/// The components of a type, by the entity they belong to.
+trait Store: Send {
+    fn remove(&mut self, entity: Entity);
+    fn entities(&self) -> Vec<Entity>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Component> Store for BTreeMap<Entity, T> {
+    fn remove(&mut self, entity: Entity) {
+        BTreeMap::remove(self, &entity);
+    }
+    fn entities(&self) -> Vec<Entity> {
+        self.keys().copied().collect()
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Every component of every entity, a table per type. Entities with a component are
+/// always visited in the order they were spawned.
+#[derive(Default)]
+pub struct Components {
+    stores: HashMap<TypeId, Box<dyn Store>>,
+}
+
+impl Components {
+    /// Attaches `component` to `entity`, replacing the one of the same type it had.
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T) {
+        self.stores
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(BTreeMap::<Entity, T>::new()))
+            .as_any_mut()
+            .downcast_mut::<BTreeMap<Entity, T>>()
+            .expect("Component stored under another type")
+            .insert(entity, component);
+    }
+
+    pub fn remove<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        self.store_mut::<T>()?.remove(&entity)
+    }
+
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.store::<T>()?.get(&entity)
+    }
+
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
+        self.store_mut::<T>()?.get_mut(&entity)
+    }
+
+    pub fn has<T: Component>(&self, entity: Entity) -> bool {
+        self.get::<T>(entity).is_some()
+    }
+
+    /// Entities with a `T`, for systems that change more than the `T` of each.
+    pub fn entities<T: Component>(&self) -> Vec<Entity> {
+        self.iter::<T>().map(|(entity, _)| entity).collect()
+    }
+
+    /// Number of entities with at least one component.
+    pub fn entity_count(&self) -> usize {
+        let entities: BTreeSet<Entity> = self
+            .stores
+            .values()
+            .flat_map(|store| store.entities())
+            .collect();
+        entities.len()
+    }
+
+    pub fn iter<T: Component>(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.store::<T>()
+            .into_iter()
+            .flatten()
+            .map(|(entity, component)| (*entity, component))
+    }
+
+    pub fn iter_mut<T: Component>(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.store_mut::<T>()
+            .into_iter()
+            .flatten()
+            .map(|(entity, component)| (*entity, component))
+    }
+
+    /// Calls `f` with both components of every entity that has a `T` and a `U`.
+    ///
+    /// Panics if `T` and `U` are the same type.
+    pub fn join_mut<T: Component, U: Component>(
+        &mut self,
+        mut f: impl FnMut(Entity, &mut T, &mut U),
+    ) {
+        let [first, second] = self
+            .stores
+            .get_disjoint_mut([&TypeId::of::<T>(), &TypeId::of::<U>()]);
+        let (Some(first), Some(second)) = (first, second) else {
+            return;
+        };
+        let first = first.as_any_mut().downcast_mut::<BTreeMap<Entity, T>>();
+        let second = second.as_any_mut().downcast_mut::<BTreeMap<Entity, U>>();
+        let (Some(first), Some(second)) = (first, second) else {
+            return;
+        };
+        for (entity, a) in first {
+            if let Some(b) = second.get_mut(entity) {
+                f(*entity, a, b);
+            }
+        }
+    }
+
+    /// Takes every component off `entity`.
+    pub(crate) fn despawn(&mut self, entity: Entity) {
+        for store in self.stores.values_mut() {
+            store.remove(entity);
+        }
+    }
+
+    fn store<T: Component>(&self) -> Option<&BTreeMap<Entity, T>> {
+        self.stores.get(&TypeId::of::<T>())?.as_any().downcast_ref()
+    }
+
+    fn store_mut<T: Component>(&mut self) -> Option<&mut BTreeMap<Entity, T>> {
+        self.stores
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut()
+    }
+}
+
+/// Moves an entity a whole cell in `direction` every time the time since its last move
+/// adds up to its speed. `Movement` moves it at most a cell a tick.
+#[derive(Copy, Clone)]
+pub struct Velocity {
+    pub direction: Direction,
+    pub speed: Speed,
+    /// Multiplies the speed, e.g. while an effect speeds the entity up.
+    pub factor: f64,
+    /// Fraction of a cell travelled since the last move.
+    progress: f64,
+    moved: bool,
+}
+
+impl Velocity {
+    pub fn new(direction: Direction, speed: Speed) -> Velocity {
+        Velocity {
+            direction,
+            speed,
+            factor: 1.0,
+            progress: 0.0,
+            moved: false,
+        }
+    }
+
+    /// Whether the entity moved a cell in the last tick.
+    pub fn moved(&self) -> bool {
+        self.moved
+    }
+
+    /// Makes the entity wait a whole period before it moves again.
+    pub fn stop(&mut self) {
+        self.progress = 0.0;
+    }
+
+    /// Moves the entity a cell once it has travelled one. It moves a cell a tick at most, so
+    /// ticks have to come at least as often as it moves; what it travels past the next cell
+    /// when they don't is lost rather than kept for later.
+    fn advance(&mut self, position: &mut Position, interval: Duration) {
+        let speed = self.speed.get_speed_steps_per_millisecond() * self.factor;
+        self.progress += speed * interval.as_secs_f64() * 1000.0;
+        self.moved = self.progress >= 1.0;
+        if self.moved {
+            self.progress = (self.progress - 1.0).min(1.0);
+            *position = *position + self.direction * 1f64;
+        }
+    }
+}
+
+/// What an entity looks like: the glyph set's character for `part`, drawn at the entity's
+/// `Position` in the theme's style for `style`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Glyph {
+    pub part: &'static str,
+    pub style: &'static str,
+    pub layer: DrawLayer,
+}
+
+impl Glyph {
+    /// A glyph drawn in the style of the same name.
+    pub fn new(part: &'static str, layer: DrawLayer) -> Glyph {
+        Glyph::styled(part, part, layer)
+    }
+
+    pub fn styled(part: &'static str, style: &'static str, layer: DrawLayer) -> Glyph {
+        Glyph { part, style, layer }
+    }
+}
+
+/// Time an entity has left before `Lifetimes` despawns it.
+#[derive(Copy, Clone, Debug)]
+pub struct Lifetime(pub Duration);
+
+/// Game logic run every tick over the entities with the components it needs. Systems run
+/// after the objects are updated, in the order they were added to the world.
+pub trait System<A> {
+    fn run(&mut self, world: &mut World<A>, interval: Duration);
+    /// Whether the system gets `event` in `on_event`.
+    fn subscribes(&self, _event: &A) -> bool {
+        false
+    }
+    /// Handles an event the system subscribes to, after the objects that subscribe to it.
+    fn on_event(&mut self, _world: &mut World<A>, _event: A) {}
+}
+
+/// Moves the entities that have a `Velocity` and a `Position`.
+pub struct Movement;
+
+impl<A> System<A> for Movement {
+    fn run(&mut self, world: &mut World<A>, interval: Duration) {
+        world
+            .components_mut()
+            .join_mut(|_, velocity: &mut Velocity, position: &mut Position| {
+                velocity.advance(position, interval)
+            });
+    }
+}
+
+/// Counts down every `Lifetime` and despawns the entities whose time is up.
+pub struct Lifetimes;
+
+impl<A> System<A> for Lifetimes {
+    fn run(&mut self, world: &mut World<A>, interval: Duration) {
+        let mut expired = Vec::new();
+        for (entity, Lifetime(remaining)) in world.components_mut().iter_mut::<Lifetime>() {
+            *remaining = remaining.saturating_sub(interval);
+            if remaining.is_zero() {
+                expired.push(entity);
+            }
+        }
+        for entity in expired {
+            world.despawn(entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: Entity = ObjectId(1);
+    const B: Entity = ObjectId(2);
+    const C: Entity = ObjectId(3);
+
+    #[test]
+    fn components_are_kept_by_type_and_entity() {
+        let mut components = Components::default();
+        components.insert(C, Lifetime(Duration::from_secs(3)));
+        components.insert(A, Lifetime(Duration::from_secs(1)));
+        components.insert(A, Lifetime(Duration::from_secs(2)));
+        components.insert(B, Glyph::new("apple", DrawLayer::Items));
+        assert_eq!(components.entities::<Lifetime>(), [A, C]);
+        assert_eq!(
+            components.get::<Lifetime>(A).unwrap().0,
+            Duration::from_secs(2)
+        );
+        assert!(!components.has::<Glyph>(A));
+        assert_eq!(components.entity_count(), 3);
+        assert_eq!(components.remove::<Glyph>(B).unwrap().part, "apple");
+        assert!(components.remove::<Glyph>(B).is_none());
+        assert_eq!(components.entity_count(), 2);
+        components.despawn(A);
+        assert_eq!(components.entities::<Lifetime>(), [C]);
+    }
+
+    #[test]
+    fn join_visits_the_entities_with_both_components() {
+        let mut components = Components::default();
+        for (entity, x) in [(A, 1.0), (B, 2.0), (C, 3.0)] {
+            components.insert(entity, Position { x, y: 1.0 });
+        }
+        components.insert(C, Lifetime(Duration::ZERO));
+        components.insert(A, Lifetime(Duration::ZERO));
+        let mut visited = Vec::new();
+        components.join_mut(|entity, _: &mut Lifetime, position: &mut Position| {
+            visited.push((entity, position.x))
+        });
+        assert_eq!(visited, [(A, 1.0), (C, 3.0)]);
+    }
+
+    fn travel(velocity: &mut Velocity, ticks: usize, interval: Duration) -> Vec<f64> {
+        let mut position = Position { x: 0.0, y: 0.0 };
+        (0..ticks)
+            .map(|_| {
+                velocity.advance(&mut position, interval);
+                position.x
+            })
+            .collect()
+    }
+
+    #[test]
+    fn velocity_moves_a_cell_each_period() {
+        let tick = Duration::from_millis(20);
+        let speed = Speed::period_in_milliseconds(50);
+        let mut velocity = Velocity::new(Direction::right(), speed);
+        assert_eq!(travel(&mut velocity, 5, tick), [0.0, 0.0, 1.0, 1.0, 2.0]);
+        velocity.factor = 2.0;
+        velocity.stop();
+        assert_eq!(travel(&mut velocity, 4, tick), [0.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn velocity_moves_a_cell_a_tick_at_most() {
+        let speed = Speed::period_in_milliseconds(10);
+        let mut velocity = Velocity::new(Direction::right(), speed);
+        let slow = travel(&mut velocity, 3, Duration::from_millis(100));
+        assert_eq!(slow, [1.0, 2.0, 3.0]);
+        assert!(velocity.progress <= 1.0);
+        let fast = travel(&mut velocity, 3, Duration::from_millis(5));
+        assert_eq!(fast, [1.0, 2.0, 2.0]);
+    }
+}
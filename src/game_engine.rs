@@ -1,184 +1,872 @@
 extern crate termion;
 
+use crate::animation::{self, Animation, Clock, Frame, Label};
+use crate::asciicast::AsciicastWriter;
+use crate::collision::{Cell, Collider, Collision, CollisionIndex};
+use crate::debug_overlay::{DebugStats, OutputStats};
+use crate::ecs::{Components, Entity, Glyph, System};
 use crate::game_utils::Position;
+use crate::key_bindings::KeyBindings;
+use crate::style::{self, ColorMode, GlyphSet, Style, Theme};
+use crate::timers::{TimerId, Timers};
 use std::any::Any;
-use std::io::{stdin, stdout, StdoutLock, Write};
-use std::process;
+use std::collections::VecDeque;
+use std::io::{stdin, stdout, Write};
+use std::ops::RangeInclusive;
+use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender};
-use std::sync::{mpsc, Arc, Mutex};
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
-use termion::event::Key;
 use termion::input::TermRead;
-use termion::raw::{IntoRawMode, RawTerminal};
+use termion::raw::IntoRawMode;
 use termion::screen::AlternateScreen;
 
-const TERMINAL_WIDTH: usize = 60;
-const TERMINAL_HEIGHT: usize = 30;
+pub const TERMINAL_WIDTH: usize = 60;
+pub const TERMINAL_HEIGHT: usize = 30;
 
 const TITLE_POSITION_X: usize = 1;
 const TITLE_POSITION_Y: usize = 1;
-const TITLE_WIDTH: usize = TERMINAL_WIDTH;
 const TITLE_HEIGHT: usize = 3;
 
 const HUD_POSITION_X: usize = 1;
 const HUD_POSITION_Y: usize = TITLE_POSITION_Y + TITLE_HEIGHT - 1;
-const HUD_WIDTH: usize = TERMINAL_WIDTH;
 const HUD_HEIGHT: usize = 6;
 
 const GAME_POSITION_X: usize = 1;
 const GAME_POSITION_Y: usize = HUD_POSITION_Y + HUD_HEIGHT - 1;
-const GAME_WIDTH: usize = TERMINAL_WIDTH;
-const GAME_HEIGHT: usize = TERMINAL_HEIGHT - HUD_HEIGHT - TITLE_HEIGHT;
 
-pub const GAME_AREA_WIDTH: usize = GAME_WIDTH - 2;
-pub const GAME_AREA_HEIGHT: usize = GAME_HEIGHT - 2;
+/// Board size of the default layout, which fills a `TERMINAL_WIDTH` x `TERMINAL_HEIGHT`
+/// terminal.
+pub const GAME_AREA_WIDTH: usize = TERMINAL_WIDTH - 2;
+pub const GAME_AREA_HEIGHT: usize = TERMINAL_HEIGHT - HUD_HEIGHT - TITLE_HEIGHT - 2;
 
-type GameSender = Arc<Mutex<Option<Sender<GameData>>>>;
+/// Ticks per second unless `EngineConfig::tick_rate` says otherwise, and the rates allowed.
+pub const DEFAULT_TICK_RATE: usize = 50;
+pub const TICK_RATES: RangeInclusive<usize> = 10..=250;
 
-pub type GameScreen<'a> = AlternateScreen<RawTerminal<StdoutLock<'a>>>;
+/// Frames per second the game thread draws at most, unless `EngineConfig::fps` says
+/// otherwise, and the rates allowed.
+pub const DEFAULT_FPS: usize = 12;
+pub const FPS_RATES: RangeInclusive<usize> = 1..=60;
+
+/// Ticks the game thread runs at once to catch up with the clock. Ticks it falls further
+/// behind by are dropped, and the game slows down rather than running in bursts.
+const MAX_CATCH_UP_TICKS: usize = 5;
+
+/// Engine settings that have to be known before the game starts.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct EngineConfig {
+    /// Size of the game area in cells. The title and the HUD are as wide as the board.
+    pub board_width: usize,
+    pub board_height: usize,
+    /// Draws the frames drawn in the last second in the title bar.
+    pub show_fps: bool,
+    /// Frames the game thread draws per second, at most.
+    pub fps: usize,
+    /// Simulation updates per second. Every tick advances the world by exactly
+    /// `tick_interval`, so a run is reproducible from its inputs and their tick numbers.
+    pub tick_rate: usize,
+    /// Colors the terminal can show. Styles are reduced to them when drawn.
+    pub color_mode: ColorMode,
+    /// Columns a board cell takes, 2 for glyph sets with emoji.
+    pub cell_width: usize,
+}
+
+impl EngineConfig {
+    pub fn terminal_width(&self) -> usize {
+        self.board_width * self.cell_width + 2
+    }
+
+    /// Rows used by the title, the HUD and the board, borders included.
+    pub fn terminal_height(&self) -> usize {
+        GAME_POSITION_Y + self.board_height + 1
+    }
+
+    /// Simulation time between two ticks.
+    pub fn tick_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.tick_rate as u32
+    }
+
+    /// Time between two frames drawn by the game thread.
+    pub fn frame_interval(&self) -> Duration {
+        Duration::from_secs(1) / self.fps as u32
+    }
+
+    fn game_height(&self) -> usize {
+        self.board_height + 2
+    }
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        EngineConfig {
+            board_width: GAME_AREA_WIDTH,
+            board_height: GAME_AREA_HEIGHT,
+            show_fps: false,
+            fps: DEFAULT_FPS,
+            tick_rate: DEFAULT_TICK_RATE,
+            color_mode: ColorMode::detect(),
+            cell_width: 1,
+        }
+    }
+}
+
+/// An object in a game. Objects belong to the game's `World` and are reached by `ObjectId`.
+pub type BoxedObject<A> = Box<dyn GameObject<Item = A> + Send>;
+type BoxedScene<A> = Box<dyn GameScene<A> + Send>;
+type BoxedSystem<A> = Box<dyn System<A> + Send>;
+type BoxedRecorder = Box<dyn InputRecorder + Send>;
+
+/// Everything drawn goes through the screen: to the terminal, to an asciicast recording, or
+/// both. A frame ends with `flush`, which hands it to the thread that owns the terminal.
+pub struct GameScreen {
+    terminal: Option<Sender<Vec<u8>>>,
+    /// What was drawn since the last `flush`, while there is a terminal.
+    frame: Vec<u8>,
+    /// Bytes drawn since the last `take_written`.
+    written: usize,
+    output: Arc<OutputStats>,
+    cast_writer: Option<AsciicastWriter>,
+    theme: Theme,
+    glyphs: GlyphSet,
+    config: EngineConfig,
+}
+
+impl GameScreen {
+    /// The theme's style for `part`, e.g. `"title"`.
+    pub fn style(&self, part: &str) -> Style {
+        self.theme.style(part)
+    }
+
+    /// The glyph set's character for `part`, e.g. `"apple"`.
+    pub fn glyph(&self, part: &str) -> String {
+        self.glyphs.glyph(part).to_string()
+    }
+
+    /// `text` in the theme's style for `part`.
+    fn paint(&self, part: &str, text: &str) -> String {
+        self.style(part).paint(text, self.config.color_mode)
+    }
+
+    fn take_written(&mut self) -> usize {
+        std::mem::take(&mut self.written)
+    }
+
+    fn set_time(&mut self, time: Duration) {
+        if let Some(cast_writer) = &mut self.cast_writer {
+            cast_writer.set_time(time);
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(cast_writer) = &mut self.cast_writer {
+            cast_writer.finish().unwrap();
+        }
+        // The output thread restores the terminal once it has no more frames to write
+        self.terminal.take();
+    }
+}
+
+impl Write for GameScreen {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(cast_writer) = &mut self.cast_writer {
+            cast_writer.capture(buf);
+        }
+        self.written += buf.len();
+        if self.terminal.is_some() {
+            self.frame.extend_from_slice(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if let Some(cast_writer) = &mut self.cast_writer {
+            cast_writer.frame()?;
+        }
+        if let Some(terminal) = &self.terminal {
+            terminal
+                .send(std::mem::take(&mut self.frame))
+                .map_err(|_| std::io::Error::from(std::io::ErrorKind::BrokenPipe))?;
+            self.output.sent();
+        }
+        Ok(())
+    }
+}
+
+/// Layers objects are drawn in, bottom first. Objects in the same layer are drawn in the
+/// order they were added, so the last one wins where they overlap.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum DrawLayer {
+    /// Scenery, such as walls.
+    Background,
+    /// Things lying on the board, such as food.
+    Items,
+    /// What moves, such as the player and enemies.
+    #[default]
+    Actors,
+    /// Short-lived visual effects.
+    Effects,
+    /// Text and markers drawn over the game.
+    Overlay,
+}
 
 pub trait Draw {
     fn draw(&self, screen: &mut GameScreen);
     fn get_position(&self) -> Position;
+    fn layer(&self) -> DrawLayer {
+        DrawLayer::default()
+    }
 }
 
 pub trait Update {
     fn update(&mut self, time_since_last_call: Duration);
 }
 
+/// Events a game's scene and objects send each other through the engine, usually an enum.
+pub trait GameEvent: Copy + std::fmt::Debug + Send + 'static {
+    /// Whether recorders get the event. Events sent every tick can leave logs alone.
+    fn logged(&self) -> bool {
+        true
+    }
+}
+
 pub trait GameObject: Draw + Update {
     type Item;
+    /// Handles the player's input.
     fn action(&mut self, action: Action);
-    fn scene_action(&mut self, action: Self::Item);
+    /// Whether the object gets `event` in `on_event`.
+    fn subscribes(&self, _event: &Self::Item) -> bool {
+        false
+    }
+    /// Handles an event the object subscribes to.
+    fn on_event(&mut self, _event: Self::Item) {}
     fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// Called once the object is in the game, with the id it was given.
+    fn on_added(&mut self, _id: ObjectId) {}
+    /// Called once the object is out of the game.
+    fn on_removed(&mut self) {}
+    /// Cells the object takes for collision detection, or `None` if nothing collides with it.
+    fn collider(&self) -> Option<Collider> {
+        None
+    }
+}
+
+/// Identifies an object or an entity for as long as it is in a game. Ids aren't reused.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct ObjectId(pub(crate) u64);
+
+enum ObjectChange<A> {
+    Add(ObjectId, BoxedObject<A>),
+    Remove(ObjectId),
 }
 
-#[derive(Copy, Clone)]
+/// Everything a scene plays with: its objects in the order they were added, its entities
+/// and the systems that run them, the events sent between them and the collisions found in
+/// the last tick.
+///
+/// The world belongs to the thread that runs the game and is only changed through `&mut`,
+/// by the engine and by the scene it is handed to. Objects added or removed while a tick
+/// runs only join or leave the game once it is over, so every object of a tick is updated
+/// and drawn with the same set of objects. Entities are plain data and change right away.
+pub struct World<A> {
+    objects: Vec<(ObjectId, BoxedObject<A>)>,
+    components: Components,
+    systems: Vec<BoxedSystem<A>>,
+    /// Events sent and not yet dispatched.
+    events: VecDeque<A>,
+    pending: Vec<ObjectChange<A>>,
+    timers: Timers<A>,
+    next_id: u64,
+    ticking: bool,
+    /// Ticks run so far, that recorded inputs and events are stamped with.
+    tick: u64,
+    collisions: CollisionIndex,
+    recorder: Option<BoxedRecorder>,
+    config: EngineConfig,
+}
+
+impl<A> World<A> {
+    fn new(config: EngineConfig, recorder: Option<BoxedRecorder>) -> World<A> {
+        World {
+            objects: Vec::new(),
+            components: Components::default(),
+            systems: Vec::new(),
+            events: VecDeque::new(),
+            pending: Vec::new(),
+            timers: Timers::new(),
+            next_id: 0,
+            ticking: false,
+            tick: 0,
+            collisions: CollisionIndex::default(),
+            recorder,
+            config,
+        }
+    }
+
+    /// Takes everything out of the game, objects, entities, systems, timers and events, and
+    /// starts counting ticks again, for a scene to be loaded into it afresh. Keeps the
+    /// config and the recorder.
+    pub fn clear(&mut self) {
+        for (_, mut object) in std::mem::take(&mut self.objects) {
+            object.on_removed();
+        }
+        *self = World::new(self.config, self.recorder.take());
+    }
+
+    /// Adds `object` to the game, at the end of the current tick if one is running.
+    pub fn add_object(&mut self, object: BoxedObject<A>) -> ObjectId {
+        self.next_id += 1;
+        let id = ObjectId(self.next_id);
+        self.change(ObjectChange::Add(id, object));
+        id
+    }
+
+    /// Takes the object out of the game, at the end of the current tick if one is running.
+    /// Does nothing if there is no such object.
+    pub fn remove_object(&mut self, id: ObjectId) {
+        self.change(ObjectChange::Remove(id));
+    }
+
+    fn change(&mut self, change: ObjectChange<A>) {
+        self.pending.push(change);
+        if !self.ticking {
+            self.apply();
+        }
+    }
+
+    /// Makes the pending changes and calls the objects' hooks.
+    fn apply(&mut self) {
+        for change in std::mem::take(&mut self.pending) {
+            match change {
+                ObjectChange::Add(id, mut object) => {
+                    object.on_added(id);
+                    self.objects.push((id, object));
+                }
+                ObjectChange::Remove(id) => {
+                    let index = self
+                        .objects
+                        .iter()
+                        .position(|(object_id, _)| *object_id == id);
+                    if let Some(index) = index {
+                        self.objects.remove(index).1.on_removed();
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn object(&self, id: ObjectId) -> Option<&BoxedObject<A>> {
+        self.objects
+            .iter()
+            .find(|(object_id, _)| *object_id == id)
+            .map(|(_, object)| object)
+    }
+
+    pub fn object_mut(&mut self, id: ObjectId) -> Option<&mut BoxedObject<A>> {
+        self.objects
+            .iter_mut()
+            .find(|(object_id, _)| *object_id == id)
+            .map(|(_, object)| object)
+    }
+
+    /// The object as a `T`, or `None` if there is no such object or it isn't a `T`.
+    pub fn get<T: 'static>(&self, id: ObjectId) -> Option<&T> {
+        self.object(id)?.as_any().downcast_ref::<T>()
+    }
+
+    pub fn get_mut<T: 'static>(&mut self, id: ObjectId) -> Option<&mut T> {
+        self.object_mut(id)?.as_any_mut().downcast_mut::<T>()
+    }
+
+    /// Ids of the objects in the game, in the order they were added.
+    pub fn object_ids(&self) -> Vec<ObjectId> {
+        self.objects.iter().map(|(id, _)| *id).collect()
+    }
+
+    /// Creates an entity without components. Entities with a `Glyph` and a `Position` are
+    /// drawn, and those with a `Collider` collide, like objects.
+    pub fn spawn(&mut self) -> Entity {
+        self.next_id += 1;
+        ObjectId(self.next_id)
+    }
+
+    /// Takes every component off `entity`.
+    pub fn despawn(&mut self, entity: Entity) {
+        self.components.despawn(entity);
+    }
+
+    pub fn components(&self) -> &Components {
+        &self.components
+    }
+
+    pub fn components_mut(&mut self) -> &mut Components {
+        &mut self.components
+    }
+
+    /// Runs `system` every tick, after the objects are updated and the systems added before.
+    pub fn add_system(&mut self, system: BoxedSystem<A>) {
+        self.systems.push(system);
+    }
+
+    /// Calls `f` with each system and the world, which doesn't hold them meanwhile. Systems
+    /// added by `f` come after the others.
+    fn with_systems(&mut self, mut f: impl FnMut(&mut BoxedSystem<A>, &mut World<A>)) {
+        let mut systems = std::mem::take(&mut self.systems);
+        for system in &mut systems {
+            f(system, self);
+        }
+        systems.append(&mut self.systems);
+        self.systems = systems;
+    }
+
+    /// Makes heads of objects in layer `a` collide with objects in layer `b`, and the other
+    /// way round.
+    pub fn add_collision_rule(&mut self, a: &'static str, b: &'static str) {
+        self.collisions.add_rule(a, b);
+    }
+
+    /// Collisions between objects after they were last updated. A tick finds them before it
+    /// updates the scene.
+    pub fn collisions(&self) -> &[Collision] {
+        self.collisions.collisions()
+    }
+
+    /// Objects that took `cell` after they were last updated.
+    pub fn objects_at(&self, cell: Cell) -> Vec<ObjectId> {
+        self.collisions.objects_at(cell)
+    }
+
+    /// Sends `event` to the objects and the scene that subscribe to it.
+    ///
+    /// Events are queued and dispatched at set points: after the objects are updated, after
+    /// the scene is updated and after an input is handled.
+    pub fn publish(&mut self, event: A) {
+        self.events.push_back(event);
+    }
+
+    /// Publishes `event` once `delay` of game time has passed. Time only passes while the
+    /// scene runs, so timers stand still while it is paused and replays see them go off on
+    /// the same ticks. A timer set during a tick starts counting with the next one.
+    pub fn after(&mut self, delay: Duration, event: A) -> TimerId {
+        self.timers.after(delay, event, !self.ticking)
+    }
+
+    /// Publishes `event` every `period` of game time until the timer is cancelled, see
+    /// `after`.
+    ///
+    /// Panics if `period` is zero.
+    pub fn every(&mut self, period: Duration, event: A) -> TimerId {
+        self.timers.every(period, event, !self.ticking)
+    }
+
+    /// Stops a timer before it goes off. Returns whether it was still set.
+    pub fn cancel_timer(&mut self, timer: TimerId) -> bool {
+        self.timers.cancel(timer)
+    }
+
+    /// Stops a timer's time, keeping what it has left, until `resume_timer`.
+    pub fn pause_timer(&mut self, timer: TimerId) {
+        self.timers.pause(timer);
+    }
+
+    pub fn resume_timer(&mut self, timer: TimerId) {
+        self.timers.resume(timer);
+    }
+
+    /// Time until the timer goes off next, or `None` if it went off for the last time or
+    /// was cancelled.
+    pub fn timer_remaining(&self, timer: TimerId) -> Option<Duration> {
+        self.timers.remaining(timer)
+    }
+
+    /// Ticks run since the game started.
+    pub fn ticks(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn config(&self) -> EngineConfig {
+        self.config
+    }
+
+    /// Moves the animations, tweens and motions that follow `clock` on by `interval`, and
+    /// despawns the entities whose animation asked for it once over.
+    fn animate(&mut self, clock: Clock, interval: Duration) {
+        for entity in animation::advance(&mut self.components, clock, interval) {
+            self.despawn(entity);
+        }
+    }
+
+    fn detect_collisions(&mut self) {
+        let colliders = self
+            .objects
+            .iter()
+            .filter_map(|(id, object)| Some((*id, object.collider()?)));
+        let entities = self
+            .components
+            .iter::<Collider>()
+            .map(|(entity, collider)| (entity, collider.clone()));
+        self.collisions.build(colliders.chain(entities));
+    }
+}
+
+impl<A: GameEvent> World<A> {
+    /// Moves the timers on by `interval` and publishes the events of those that went off.
+    fn advance_timers(&mut self, interval: Duration) {
+        for event in self.timers.advance(interval) {
+            self.publish(event);
+        }
+    }
+
+    /// Hands every event sent so far, and those sent while handling them, to the objects,
+    /// the systems and then `scene` if they subscribe to it, in the order they were sent.
+    ///
+    /// Ticks and inputs do it on their own. Scenes that send events at other times, e.g.
+    /// when they are reset, can call it themselves.
+    pub fn dispatch_events(&mut self, scene: &mut dyn GameScene<A>) {
+        while let Some(event) = self.events.pop_front() {
+            if let (true, Some(recorder)) = (event.logged(), &mut self.recorder) {
+                recorder.record_event(self.tick, &format!("{:?}", event));
+            }
+            for (_, object) in &mut self.objects {
+                if object.subscribes(&event) {
+                    object.on_event(event);
+                }
+            }
+            self.with_systems(|system, world| {
+                if system.subscribes(&event) {
+                    system.on_event(world, event);
+                }
+            });
+            if scene.subscribes(&event) {
+                scene.on_event(self, event);
+            }
+        }
+    }
+
+    /// Runs a tick of `scene`: updates every object, runs the systems, finds the collisions
+    /// between objects and entities, updates the scene by `interval` and then moves the
+    /// timers and the game-time animations on, unless the scene is paused.
+    ///
+    /// A scene can run ticks of a scene it wraps between two ticks, from its `input` or
+    /// `after_tick`, e.g. to seek. It must not from its `update`, where the timers of the
+    /// tick being run haven't gone off yet.
+    pub fn step(&mut self, scene: &mut dyn GameScene<A>, interval: Duration) {
+        if scene.paused() {
+            return;
+        }
+        let ticking = std::mem::replace(&mut self.ticking, true);
+        for (_, object) in &mut self.objects {
+            object.update(interval);
+        }
+        self.with_systems(|system, world| system.run(world, interval));
+        self.dispatch_events(scene);
+        self.detect_collisions();
+        scene.update(self, interval);
+        self.advance_timers(interval);
+        self.animate(Clock::Simulation, interval);
+        self.dispatch_events(scene);
+        self.ticking = ticking;
+        if !ticking {
+            self.apply();
+        }
+        self.tick += 1;
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Action {
     MoveUp,
     MoveDown,
     MoveRight,
     MoveLeft,
     Command(char),
+    /// Starts the game over. A key of its own, so rebinding it frees the old one.
+    Restart,
+    /// Shows or hides the debug overlay. The engine handles it, scenes never get it.
+    ToggleDebug,
     Quit,
 }
 
-#[derive(Copy, Clone)]
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::MoveUp => write!(f, "up"),
+            Action::MoveDown => write!(f, "down"),
+            Action::MoveRight => write!(f, "right"),
+            Action::MoveLeft => write!(f, "left"),
+            Action::Command(c) => write!(f, "char:{}", c),
+            Action::Restart => write!(f, "restart"),
+            Action::ToggleDebug => write!(f, "debug"),
+            Action::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+impl std::str::FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(Action::MoveUp),
+            "down" => Ok(Action::MoveDown),
+            "right" => Ok(Action::MoveRight),
+            "left" => Ok(Action::MoveLeft),
+            "restart" => Ok(Action::Restart),
+            "debug" => Ok(Action::ToggleDebug),
+            "quit" => Ok(Action::Quit),
+            _ => {
+                let mut chars = s.strip_prefix("char:").unwrap_or_default().chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(Action::Command(c)),
+                    _ => Err(format!("unknown action `{}`", s)),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
 pub struct GameData {
     pub action: Action,
 }
 
 pub trait GameScene<A> {
-    fn set_game_engine(&mut self, game_engine: Arc<Mutex<Game<A>>>);
-    fn load(&self);
-    fn update(&self, interval: Duration);
-    fn draw_hud(&self, width: usize, height: usize) -> Vec<String>;
+    /// Adds the scene's objects to `world`, before the first tick.
+    fn load(&mut self, world: &mut World<A>);
+    fn update(&mut self, world: &mut World<A>, interval: Duration);
+    fn draw_hud(&self, world: &World<A>, width: usize, height: usize) -> Vec<String>;
     fn draw_title(&self, width: usize, height: usize) -> String;
-    fn input(&self, game_data: GameData);
+    fn input(&mut self, world: &mut World<A>, game_data: GameData);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// While a scene is paused neither it nor its objects are updated.
+    fn paused(&self) -> bool {
+        false
+    }
+    /// Whether the scene gets `event` in `on_event`.
+    fn subscribes(&self, _event: &A) -> bool {
+        false
+    }
+    /// Handles an event the scene subscribes to, after the objects that subscribe to it.
+    fn on_event(&mut self, _world: &mut World<A>, _event: A) {}
+    /// Ticks `Simulation::tick` runs each time, more than one to fast-forward.
+    fn speed(&self) -> u32 {
+        1
+    }
+    /// Called after every tick `Simulation::tick` runs, between ticks like the inputs of a
+    /// live game, e.g. for a replay to send the recorded ones.
+    fn after_tick(&mut self, _world: &mut World<A>) {}
 }
 
-pub struct Game<A> {
-    objects: Arc<Mutex<Vec<Arc<Mutex<Box<dyn GameObject<Item = A> + Send>>>>>>,
-    game_scene: Arc<Mutex<Box<dyn GameScene<A> + Send + 'static>>>,
-    main_thread_sender: GameSender,
-    input_thread_sender: GameSender,
-    game_thread_sender: GameSender,
-    input_thread: Option<thread::JoinHandle<()>>,
-    game_thread: Option<thread::JoinHandle<()>>,
+/// Receives every `GameData` the game thread handles, with the tick it arrived at.
+pub trait InputRecorder {
+    fn record(&mut self, tick: u64, game_data: GameData);
+    /// Receives every logged `GameEvent` as it is dispatched, written with `Debug`.
+    fn record_event(&mut self, _tick: u64, _event: &str) {}
+    /// Called once the game ends, with the first error met while recording, if any.
+    fn finish(&mut self) -> Result<(), String>;
 }
 
-impl<A: 'static> Game<A> {
-    pub fn new<T: GameScene<A> + Send + 'static>(
-        game_scene: Box<T>,
-        main_thread_sender: Arc<Mutex<Option<Sender<GameData>>>>,
-    ) -> Arc<Mutex<Game<A>>> {
-        let game = Game {
-            objects: Arc::new(Mutex::new(Vec::new())),
-            game_scene: Arc::new(Mutex::new(game_scene)),
-            main_thread_sender: Arc::clone(&main_thread_sender),
-            input_thread_sender: Arc::new(Mutex::new(None)),
-            game_thread_sender: Arc::new(Mutex::new(None)),
-            input_thread: None,
-            game_thread: None,
-        };
-        let game = Arc::new(Mutex::new(game));
-        let game_clone = Arc::clone(&game);
-        game.lock()
-            .unwrap()
-            .game_scene
-            .lock()
-            .unwrap()
-            .set_game_engine(game_clone);
-        game
-    }
-
-    pub fn start(&mut self) -> (GameSender, GameSender) {
-        // Channel to transmit data to game thread
-        let (game_sender, game_receiver) = mpsc::channel::<GameData>();
-        *self.game_thread_sender.lock().unwrap() = Some(game_sender);
-        // Channel to transmit data to input thread
-        let (input_sender, input_receiver) = mpsc::channel::<GameData>();
-        *self.input_thread_sender.lock().unwrap() = Some(input_sender);
+/// Runs a scene and its world on the calling thread, without a terminal.
+///
+/// `game_thread` drives one for every tick, and it can also be used directly to run a
+/// scene headless, e.g. for bots or training environments.
+pub struct Simulation<A> {
+    world: World<A>,
+    scene: BoxedScene<A>,
+    theme: Theme,
+    glyphs: GlyphSet,
+}
 
-        let main_sender = Arc::clone(&self.main_thread_sender);
-        let game_sender = Arc::clone(&self.game_thread_sender);
+impl<A: GameEvent> Simulation<A> {
+    pub fn load(&mut self) {
+        self.scene.load(&mut self.world);
+    }
 
-        let thread = thread::spawn(move || {
-            Self::input_thread(main_sender, game_sender, input_receiver);
-        });
-        self.input_thread = Some(thread);
-        let objects = Arc::clone(&self.objects);
-        let game_scene = Arc::clone(&self.game_scene);
-        let main_sender = Arc::clone(&self.main_thread_sender);
-        let input_sender = Arc::clone(&self.input_thread_sender);
+    /// Runs as many ticks as the scene's `speed`, see `World::step`, each followed by the
+    /// scene's `after_tick`. Objects added or removed meanwhile join or leave the game at
+    /// the end of the tick.
+    pub fn tick(&mut self, interval: Duration) {
+        for _ in 0..self.scene.speed() {
+            self.world.step(&mut *self.scene, interval);
+            self.scene.after_tick(&mut self.world);
+            self.dispatch_events();
+        }
+    }
+
+    pub fn input(&mut self, game_data: GameData) {
+        if let Some(recorder) = &mut self.world.recorder {
+            recorder.record(self.world.tick, game_data);
+        }
+        self.scene.input(&mut self.world, game_data);
+        self.dispatch_events();
+    }
+
+    /// Stops recording inputs, returning the recorder's error if it couldn't keep up.
+    pub fn finish_recording(&mut self) -> Result<(), String> {
+        match self.world.recorder.take() {
+            Some(mut recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
 
-        let thread = thread::spawn(move || {
+    /// Dispatches the events sent since the last time. Ticks and inputs do it on their own.
+    pub fn dispatch_events(&mut self) {
+        self.world.dispatch_events(&mut *self.scene);
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.world.tick
+    }
+
+    pub fn config(&self) -> EngineConfig {
+        self.world.config
+    }
+
+    pub fn world(&self) -> &World<A> {
+        &self.world
+    }
+
+    /// Gives access to the concrete scene and its world, or `None` if the scene isn't a `T`.
+    pub fn with_scene<T: 'static, R>(&self, f: impl FnOnce(&T, &World<A>) -> R) -> Option<R> {
+        let scene = self.scene.as_any().downcast_ref::<T>()?;
+        Some(f(scene, &self.world))
+    }
+
+    /// Like `with_scene`, for changing the scene or its world between ticks.
+    pub fn with_scene_mut<T: 'static, R>(
+        &mut self,
+        f: impl FnOnce(&mut T, &mut World<A>) -> R,
+    ) -> Option<R> {
+        let scene = self.scene.as_any_mut().downcast_mut::<T>()?;
+        Some(f(scene, &mut self.world))
+    }
+}
+
+/// A scene set up to run in the terminal.
+///
+/// Once started, one thread reads the keyboard, one owns the scene and its world and runs
+/// them, and one writes the frames to the terminal. Only inputs and frames cross between
+/// them, over channels, besides the counters the debug overlay shows.
+pub struct Game<A> {
+    scene: BoxedScene<A>,
+    input_recorder: Option<BoxedRecorder>,
+    config: EngineConfig,
+    key_bindings: KeyBindings,
+    theme: Theme,
+    glyphs: GlyphSet,
+    cast_writer: Option<AsciicastWriter>,
+}
+
+impl<A: GameEvent> Game<A> {
+    pub fn new<T: GameScene<A> + Send + 'static>(game_scene: Box<T>) -> Game<A> {
+        Game {
+            scene: game_scene,
+            input_recorder: None,
+            config: EngineConfig::default(),
+            key_bindings: KeyBindings::default(),
+            theme: Theme::default(),
+            glyphs: GlyphSet::default(),
+            cast_writer: None,
+        }
+    }
+
+    /// Creates a game for `game_scene`, starts it and blocks until the player quits.
+    ///
+    /// `setup` can configure the engine, e.g. set recorders, before its threads start. Fails
+    /// if the input recorder couldn't write the game down.
+    pub fn run<T: GameScene<A> + Send + 'static>(
+        game_scene: Box<T>,
+        setup: impl FnOnce(&mut Game<A>),
+    ) -> Result<(), String> {
+        let mut game = Game::new(game_scene);
+        setup(&mut game);
+        game.start()
+    }
+
+    /// Starts the game's threads and waits for them to end, once the player quits.
+    fn start(mut self) -> Result<(), String> {
+        let (game_sender, game_receiver) = mpsc::channel::<GameData>();
+        let (frame_sender, frame_receiver) = mpsc::channel::<Vec<u8>>();
+        let output = Arc::new(OutputStats::default());
+        let output_stats = output.clone();
+        let output_thread = thread::spawn(move || Self::output_thread(frame_receiver, output));
+        let key_bindings = self.key_bindings.clone();
+        let input_thread = thread::spawn(move || Self::input_thread(game_sender, key_bindings));
+        let cast_writer = self.cast_writer.take();
+        let simulation = self.simulation();
+        let game_thread = thread::spawn(move || {
             Self::game_thread(
-                objects,
-                game_scene,
-                main_sender,
-                input_sender,
+                simulation,
+                cast_writer,
+                frame_sender,
+                output_stats,
                 game_receiver,
-            );
+            )
         });
-        self.game_thread = Some(thread);
+        let result = game_thread.join().unwrap();
+        output_thread.join().unwrap();
+        input_thread.join().unwrap();
+        result
+    }
 
-        (
-            Arc::clone(&self.input_thread_sender),
-            Arc::clone(&self.game_thread_sender),
-        )
+    pub fn set_input_recorder(&mut self, recorder: Box<dyn InputRecorder + Send>) {
+        self.input_recorder = Some(recorder);
     }
 
-    pub fn add_object(&mut self, object: Arc<Mutex<Box<dyn GameObject<Item = A> + Send>>>) {
-        self.objects.lock().unwrap().push(object);
+    /// Sets the board size. Has to be called before the scene is loaded.
+    pub fn set_config(&mut self, config: EngineConfig) {
+        self.config = config;
     }
 
-    fn input_thread(
-        main_thread_sender: GameSender,
-        game_sender: GameSender,
-        _input_receiver: Receiver<GameData>,
-    ) {
+    pub fn config(&self) -> EngineConfig {
+        self.config
+    }
+
+    /// Keys the input thread reads once the game starts.
+    pub fn set_key_bindings(&mut self, key_bindings: KeyBindings) {
+        self.key_bindings = key_bindings;
+    }
+
+    /// Styles for the title, the HUD and the borders, and for the parts objects draw.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Characters objects draw for their parts. `EngineConfig::cell_width` has to fit them.
+    pub fn set_glyphs(&mut self, glyphs: GlyphSet) {
+        self.glyphs = glyphs;
+    }
+
+    /// Records everything the game thread draws once the game starts.
+    pub fn set_cast_writer(&mut self, cast_writer: AsciicastWriter) {
+        self.cast_writer = Some(cast_writer);
+    }
+
+    /// Turns the game into a simulation of its scene, to run on the calling thread.
+    pub fn simulation(self) -> Simulation<A> {
+        Simulation {
+            world: World::new(self.config, self.input_recorder),
+            scene: self.scene,
+            theme: self.theme,
+            glyphs: self.glyphs,
+        }
+    }
+
+    fn input_thread(game_sender: Sender<GameData>, key_bindings: KeyBindings) {
         let stdin = stdin();
         let stdin = stdin.lock();
         let mut stdin = stdin.keys();
 
         loop {
-            let action = match stdin.next().unwrap().unwrap() {
-                Key::Esc => Action::Quit,
-                Key::Up => Action::MoveUp,
-                Key::Down => Action::MoveDown,
-                Key::Left => Action::MoveLeft,
-                Key::Right => Action::MoveRight,
-                Key::Char(c) => Action::Command(c),
-                _ => continue,
+            let action = match key_bindings.action(stdin.next().unwrap().unwrap()) {
+                Some(action) => action,
+                None => continue,
             };
-            let game_data = GameData { action };
-            if let Some(sender) = &*game_sender.lock().unwrap() {
-                sender.send(game_data).unwrap();
-            }
-            if let Some(sender) = &*main_thread_sender.lock().unwrap() {
-                sender.send(game_data).unwrap();
+            if game_sender.send(GameData { action }).is_err() {
+                break;
             }
             if let Action::Quit = action {
                 break;
@@ -186,43 +874,197 @@ impl<A: 'static> Game<A> {
         }
     }
 
+    /// Writes the frames it receives to the terminal, and restores the terminal once the
+    /// game thread stops sending them.
+    fn output_thread(frames: Receiver<Vec<u8>>, stats: Arc<OutputStats>) {
+        let stdout = stdout();
+        let stdout = stdout.lock();
+        let stdout = stdout.into_raw_mode().unwrap();
+        let mut terminal = AlternateScreen::from(stdout);
+        for frame in frames {
+            let start = Instant::now();
+            terminal.write_all(&frame).unwrap();
+            terminal.flush().unwrap();
+            stats.written(start.elapsed());
+        }
+        terminal.suspend_raw_mode().unwrap();
+        // Leaving the alternate screen isn't flushed on drop, so errors printed once the game
+        // ends would land on it and be wiped
+        drop(terminal);
+        std::io::stdout().flush().unwrap();
+    }
+
     pub fn draw_point(screen: &mut GameScreen, c: &str, x: usize, y: usize) {
+        Self::draw_styled_point(screen, c, Style::default(), x, y);
+    }
+
+    /// Draws `c` in `style`, usually one of the theme's from `GameScreen::style`. Glyphs
+    /// narrower than a cell are padded with spaces.
+    pub fn draw_styled_point(screen: &mut GameScreen, c: &str, style: Style, x: usize, y: usize) {
         if x < 1 || y < 1 {
             return;
         }
-        let x = (x + GAME_POSITION_X) as u16;
+        let cell_width = screen.config.cell_width;
+        let x = (GAME_POSITION_X + (x - 1) * cell_width + 1) as u16;
         let y = (y + GAME_POSITION_Y) as u16;
+        let padding = cell_width.saturating_sub(style::display_width(c));
+        let c = style.paint(
+            &format!("{}{}", c, " ".repeat(padding)),
+            screen.config.color_mode,
+        );
         write!(screen, "{}{}", termion::cursor::Goto(x, y), c).unwrap();
     }
 
     fn game_thread(
-        game_objects: Arc<Mutex<Vec<Arc<Mutex<Box<dyn GameObject<Item = A> + Send>>>>>>,
-        game_scene: Arc<Mutex<Box<dyn GameScene<A> + Send + 'static>>>,
-        _main_thread_sender: GameSender,
-        _input_thread_sender: GameSender,
+        mut simulation: Simulation<A>,
+        cast_writer: Option<AsciicastWriter>,
+        frame_sender: Sender<Vec<u8>>,
+        output: Arc<OutputStats>,
         game_receiver: Receiver<GameData>,
+    ) -> Result<(), String> {
+        let mut screen = GameScreen {
+            terminal: Some(frame_sender),
+            frame: Vec::new(),
+            written: 0,
+            output: output.clone(),
+            cast_writer,
+            theme: simulation.theme.clone(),
+            glyphs: simulation.glyphs.clone(),
+            config: simulation.config(),
+        };
+
+        let tick_interval = simulation.config().tick_interval();
+        let frame_interval = simulation.config().frame_interval();
+        let mut next_frame = Instant::now();
+        let mut last_frame = Instant::now();
+        let mut next_tick = Instant::now() + tick_interval;
+        let mut stats = DebugStats::new(output);
+        let mut debug = false;
+
+        simulation.load();
+        Self::draw_title_frame(&mut screen, &simulation);
+        loop {
+            // Update Game Objects
+            let mut ticks = 0;
+            while Instant::now() >= next_tick && ticks < MAX_CATCH_UP_TICKS {
+                simulation.tick(tick_interval);
+                next_tick += tick_interval;
+                ticks += 1;
+            }
+            let now = Instant::now();
+            let mut dropped = 0;
+            if now >= next_tick {
+                dropped = (now - next_tick).as_nanos() / tick_interval.as_nanos() + 1;
+                next_tick = now + tick_interval;
+            }
+            stats.ticks(ticks, dropped as usize);
+
+            // Update Screen
+            if Instant::now() >= next_frame {
+                let start = Instant::now();
+                let frame_time = start - last_frame;
+                last_frame = start;
+                simulation.world.animate(Clock::Render, frame_time);
+                if simulation.config().show_fps {
+                    Self::draw_fps(&mut screen, &simulation, stats.fps());
+                }
+                let overlay = match debug {
+                    true => {
+                        let world = &simulation.world;
+                        let entities = world.components.entity_count();
+                        stats.lines(world.objects.len(), entities, world.tick)
+                    }
+                    false => Vec::new(),
+                };
+                Self::draw_frame(&mut screen, &simulation, &overlay);
+                stats.frame(start.elapsed(), screen.take_written());
+                next_frame = start + frame_interval;
+            }
+
+            // Wait for input until the next tick or frame is due
+            let timeout = next_tick
+                .min(next_frame)
+                .saturating_duration_since(Instant::now())
+                .min(Duration::from_millis(10));
+            let start = Instant::now();
+            let received = game_receiver.recv_timeout(timeout);
+            stats.waited(start.elapsed());
+            let game_data = match received {
+                Ok(game_object) => game_object,
+                Err(_) => continue,
+            };
+            if let Action::ToggleDebug = game_data.action {
+                debug = !debug;
+                next_frame = Instant::now();
+                continue;
+            }
+            simulation.input(game_data);
+            if let Action::Quit = game_data.action {
+                break;
+            }
+        }
+
+        screen.finish();
+        simulation.finish_recording()
+    }
+
+    /// Runs the scene without a terminal, drawing a frame every `EngineConfig::frame_interval`
+    /// of simulation time into `cast_writer` until `done` returns true.
+    pub fn render_headless(
+        mut simulation: Simulation<A>,
+        cast_writer: AsciicastWriter,
+        mut done: impl FnMut(&Simulation<A>) -> bool,
     ) {
-        use termion::clear::{AfterCursor, All};
-        use termion::cursor::Goto;
+        let mut screen = GameScreen {
+            terminal: None,
+            frame: Vec::new(),
+            written: 0,
+            output: Arc::default(),
+            cast_writer: Some(cast_writer),
+            theme: simulation.theme.clone(),
+            glyphs: simulation.glyphs.clone(),
+            config: simulation.config(),
+        };
+        let tick_interval = simulation.config().tick_interval();
+        let frame_interval = simulation.config().frame_interval();
+        let mut time = Duration::ZERO;
+        let mut next_frame = Duration::ZERO;
+        let mut last_frame = Duration::ZERO;
+        screen.set_time(time);
 
-        let stdout = stdout();
-        let stdout = stdout.lock();
-        let stdout = stdout.into_raw_mode().unwrap();
+        simulation.load();
+        Self::draw_title_frame(&mut screen, &simulation);
+        loop {
+            let done = done(&simulation);
+            if done || time >= next_frame {
+                simulation.world.animate(Clock::Render, time - last_frame);
+                last_frame = time;
+                Self::draw_frame(&mut screen, &simulation, &[]);
+                next_frame += frame_interval;
+            }
+            if done {
+                break;
+            }
+            simulation.tick(tick_interval);
+            time += tick_interval;
+            screen.set_time(time);
+        }
+        screen.finish();
+    }
 
-        let mut screen = AlternateScreen::from(stdout);
+    fn draw_title_frame(screen: &mut GameScreen, simulation: &Simulation<A>) {
+        use termion::clear::All;
+        use termion::cursor::Goto;
 
-        let mut refresh_screen = true;
-        let mut refresh_interval = Instant::now();
-        let mut now = Instant::now();
+        let width = simulation.config().terminal_width();
 
-        let game_scene = game_scene.lock().unwrap();
-        game_scene.load();
         // Clean screen
         write!(screen, "{}", All).unwrap();
         write!(screen, "{}", Goto(1, 1)).unwrap();
 
         // Draw title
-        let title = game_scene.draw_title(TITLE_WIDTH - 2, TITLE_HEIGHT - 2);
+        let title = simulation.scene.draw_title(width - 2, TITLE_HEIGHT - 2);
+        let title = screen.paint("title", truncate(&title, width - 2));
         write!(
             screen,
             "{}{}",
@@ -231,91 +1073,166 @@ impl<A: 'static> Game<A> {
         )
         .unwrap();
         Self::draw_title_square(
-            &mut screen,
+            screen,
             TITLE_POSITION_X,
             TITLE_POSITION_Y,
-            TITLE_WIDTH,
+            width,
             TITLE_HEIGHT,
         );
-        loop {
-            // Update Game Objects
-            if now.elapsed().as_millis() > 100 {
-                for o in &mut *game_objects.lock().unwrap() {
-                    o.lock().unwrap().update(now.elapsed());
-                }
-                game_scene.update(now.elapsed());
-                now = Instant::now();
+    }
+
+    fn draw_fps(screen: &mut GameScreen, simulation: &Simulation<A>, fps: usize) {
+        let text = format!("{:>3} fps", fps);
+        let x = simulation
+            .config()
+            .terminal_width()
+            .saturating_sub(text.len() + 1);
+        let text = screen.paint("title", &text);
+        write!(
+            screen,
+            "{}{}",
+            termion::cursor::Goto(x.max(2) as u16, TITLE_POSITION_Y as u16 + 1),
+            text
+        )
+        .unwrap();
+    }
+
+    /// Draws the HUD and the board, with the `overlay` lines over the top of the board.
+    fn draw_frame(screen: &mut GameScreen, simulation: &Simulation<A>, overlay: &[String]) {
+        use termion::clear::AfterCursor;
+        use termion::cursor::Goto;
+
+        let width = simulation.config().terminal_width();
+
+        write!(
+            screen,
+            "{}",
+            Goto(HUD_POSITION_X as u16, HUD_POSITION_Y as u16)
+        )
+        .unwrap();
+        write!(screen, "{}", AfterCursor).unwrap();
+
+        // Draw HUD
+        let hud = simulation
+            .scene
+            .draw_hud(&simulation.world, width - 2, HUD_HEIGHT - 2);
+        for (i, text) in hud.iter().enumerate() {
+            if i > (HUD_HEIGHT - 2) {
+                break;
             }
+            let text = screen.paint("hud", truncate(text, width - 2));
+            write!(
+                screen,
+                "{}{}",
+                Goto(
+                    HUD_POSITION_X as u16 + 1,
+                    HUD_POSITION_Y as u16 + 1 + i as u16
+                ),
+                text,
+            )
+            .unwrap();
+        }
+        Self::draw_hud_square(screen, HUD_POSITION_X, HUD_POSITION_Y, width, HUD_HEIGHT);
 
-            // Update Screen
-            if refresh_screen {
-                write!(
-                    screen,
-                    "{}",
-                    termion::cursor::Goto(HUD_POSITION_X as u16, HUD_POSITION_Y as u16)
-                )
-                .unwrap();
-                write!(screen, "{}", AfterCursor).unwrap();
-
-                // Draw HUD
-                let hud = game_scene.draw_hud(HUD_WIDTH - 2, HUD_HEIGHT - 2);
-                for (i, text) in hud.iter().enumerate() {
-                    if i > (HUD_HEIGHT - 2) {
-                        break;
-                    }
-                    write!(
-                        screen,
-                        "{}{}",
-                        Goto(
-                            HUD_POSITION_X as u16 + 1,
-                            HUD_POSITION_Y as u16 + 1 + i as u16
-                        ),
-                        text,
-                    )
-                    .unwrap();
+        // Draw Objects and Entities
+        let world = &simulation.world;
+        let objects = world
+            .objects
+            .iter()
+            .map(|(id, o)| (o.layer(), *id, Drawable::Object(o)));
+        let components = &world.components;
+        // Where an entity is drawn and the frame of its animation, unless it is hidden
+        let place = |id: Entity| {
+            let frame = components
+                .get::<Animation>(id)
+                .map_or(Frame::Shown, Animation::frame);
+            let position = *components.get::<Position>(id)?;
+            (frame != Frame::Hidden).then_some((position, frame))
+        };
+        let glyphs = components.iter::<Glyph>().filter_map(|(id, glyph)| {
+            let (position, frame) = place(id)?;
+            Some((glyph.layer, id, Drawable::Glyph(glyph, position, frame)))
+        });
+        let labels = components.iter::<Label>().filter_map(|(id, label)| {
+            let (position, frame) = place(id)?;
+            Some((label.layer, id, Drawable::Label(label, position, frame)))
+        });
+        let mut drawables: Vec<_> = objects.chain(glyphs).chain(labels).collect();
+        drawables.sort_by_key(|(layer, id, _)| (*layer, *id));
+        for (_, _, drawable) in drawables {
+            match drawable {
+                Drawable::Object(o) => o.draw(screen),
+                Drawable::Glyph(glyph, position, frame) => {
+                    Self::draw_glyph(screen, glyph, position, frame)
                 }
-                Self::draw_hud_square(
-                    &mut screen,
-                    HUD_POSITION_X,
-                    HUD_POSITION_Y,
-                    HUD_WIDTH,
-                    HUD_HEIGHT,
-                );
-
-                // Draw Objects
-                for o in &*game_objects.lock().unwrap() {
-                    o.lock().unwrap().draw(&mut screen);
+                Drawable::Label(label, position, frame) => {
+                    Self::draw_label(screen, label, position, frame)
                 }
-                Self::draw_game_square(
-                    &mut screen,
-                    GAME_POSITION_X,
-                    GAME_POSITION_Y,
-                    GAME_WIDTH,
-                    GAME_HEIGHT,
-                );
-
-                write!(screen, "{}", termion::cursor::Goto(1, 1)).unwrap();
-
-                screen.flush().unwrap();
-                refresh_screen = false;
-                refresh_interval = Instant::now();
-            } else if refresh_interval.elapsed() > Duration::from_secs_f32(1f32 / 12f32) {
-                refresh_screen = true;
-            }
-
-            let game_data = match game_receiver.recv_timeout(Duration::from_millis(10)) {
-                Ok(game_object) => game_object,
-                Err(_) => continue,
-            };
-            game_scene.input(game_data);
-            if let Action::Quit = game_data.action {
-                break;
             }
         }
+        for (i, text) in overlay.iter().enumerate().take(world.config.board_height) {
+            let text = screen.paint("hud", truncate(text, width - 2));
+            write!(
+                screen,
+                "{}{}",
+                Goto(
+                    GAME_POSITION_X as u16 + 1,
+                    GAME_POSITION_Y as u16 + 1 + i as u16
+                ),
+                text,
+            )
+            .unwrap();
+        }
+        Self::draw_game_square(
+            screen,
+            GAME_POSITION_X,
+            GAME_POSITION_Y,
+            width,
+            simulation.config().game_height(),
+        );
+
+        write!(screen, "{}", Goto(1, 1)).unwrap();
+
+        screen.flush().unwrap();
+    }
 
-        screen.suspend_raw_mode().unwrap();
-        print!("{}", termion::clear::All);
-        process::exit(0);
+    fn draw_glyph(screen: &mut GameScreen, glyph: &Glyph, position: Position, frame: Frame) {
+        let part = match frame {
+            Frame::Glyph(part) => part,
+            _ => glyph.part,
+        };
+        let (x, y) = position.get_screen_coordinates();
+        let (text, style) = (screen.glyph(part), screen.style(glyph.style));
+        Self::draw_styled_point(screen, &text, style, x, y);
+    }
+
+    /// Draws the label's text centered on the cell at `position`, cut to fit the board.
+    fn draw_label(screen: &mut GameScreen, label: &Label, position: Position, frame: Frame) {
+        let text = match frame {
+            Frame::Text(text) => text,
+            _ => &label.text,
+        };
+        let (x, y) = position.get_screen_coordinates();
+        let config = screen.config;
+        if x < 1 || y < 1 || x > config.board_width || y > config.board_height {
+            return;
+        }
+        let cell_width = config.cell_width;
+        let center = GAME_POSITION_X + (x - 1) * cell_width + 1 + cell_width / 2;
+        let column = center
+            .saturating_sub(style::display_width(text) / 2)
+            .max(GAME_POSITION_X + 1);
+        let right = GAME_POSITION_X + config.board_width * cell_width;
+        let text = truncate(text, (right + 1).saturating_sub(column));
+        let text = screen.style(label.style).paint(text, config.color_mode);
+        let y = y + GAME_POSITION_Y;
+        write!(
+            screen,
+            "{}{}",
+            termion::cursor::Goto(column as u16, y as u16),
+            text
+        )
+        .unwrap();
     }
 
     fn draw_bezel(
@@ -329,14 +1246,15 @@ impl<A: 'static> Game<A> {
         use termion::cursor::Goto;
         // border should follow this order: top-left, top-right, bottom-left and bottom-right corner,
         // horizontal and vertical bars
-        let mut b = Vec::<char>::new();
+        let mut b = Vec::<String>::new();
         for c in border.chars() {
-            b.push(c);
+            b.push(String::from(c));
+        }
+        let horizontal = screen.paint("border", &b[4].repeat(width - 2));
+        for c in &mut b {
+            *c = screen.paint("border", c);
         }
-        let mut border = String::from(border);
-        border.push_str("\n\x08");
-        let horizontal = String::from(b[4]).repeat(width - 2);
-        let mut vertical = String::from(b[5]);
+        let mut vertical = b[5].clone();
         vertical.push_str("\n\x08");
         let vertical = vertical.repeat(height - 2);
 
@@ -366,15 +1284,21 @@ impl<A: 'static> Game<A> {
     }
 }
 
-impl<A> Drop for Game<A> {
-    fn drop(&mut self) {
-        let thread = self.input_thread.take();
-        if let Some(thread) = thread {
-            thread.join().unwrap();
-        }
-        let thread = self.game_thread.take();
-        if let Some(thread) = thread {
-            thread.join().unwrap();
+/// Something drawn in a frame, ordered with the rest by its layer and id.
+enum Drawable<'a, A> {
+    Object(&'a BoxedObject<A>),
+    Glyph(&'a Glyph, Position, Frame),
+    Label(&'a Label, Position, Frame),
+}
+
+/// Cuts `text` to at most `width` columns so it stays inside its box.
+fn truncate(text: &str, width: usize) -> &str {
+    let mut columns = 0;
+    for (end, c) in text.char_indices() {
+        columns += style::char_width(c);
+        if columns > width {
+            return &text[..end];
         }
     }
+    text
 }
@@ -20,16 +20,34 @@ impl Direction {
     pub fn left() -> Direction {
         Direction { x: -1, y: 0 }
     }
+
+    pub fn opposite(&self) -> Direction {
+        Direction {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        self.y == 0
+    }
+
+    /// `up`, `down`, `left` or `right`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Direction { x: 0, y: -1 } => "up",
+            Direction { x: 0, y: 1 } => "down",
+            Direction { x: 1, y: 0 } => "right",
+            Direction { x: -1, y: 0 } => "left",
+            Direction { x: _, y: _ } => "none",
+        }
+    }
 }
 
 impl PartialEq for Direction {
     fn eq(&self, other: &Self) -> bool {
         self.x == other.x && self.y == other.y
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        self.x != other.x || self.y != other.y
-    }
 }
 
 impl Mul<f64> for Direction {
@@ -59,7 +77,7 @@ impl std::fmt::Display for Direction {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
@@ -87,12 +105,6 @@ impl PartialEq for Position {
         let other_screen = other.get_screen_coordinates();
         left_screen.0 == other_screen.0 && left_screen.1 == other_screen.1
     }
-
-    fn ne(&self, other: &Self) -> bool {
-        let left_screen = self.get_screen_coordinates();
-        let other_screen = other.get_screen_coordinates();
-        left_screen.0 != other_screen.0 || left_screen.1 != other_screen.1
-    }
 }
 
 impl std::fmt::Display for Position {
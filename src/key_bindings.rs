@@ -0,0 +1,110 @@
+use crate::game_engine::Action;
+use std::collections::HashMap;
+use termion::event::Key;
+
+/// Maps the keys the input thread reads to the `Action`s sent to the game.
+///
+/// Arrows move, `Esc` quits and `F3` shows the debug overlay unless they are rebound. Other
+/// characters are sent as `Action::Command`.
+#[derive(Clone, Debug)]
+pub struct KeyBindings {
+    keys: HashMap<Key, Action>,
+}
+
+impl KeyBindings {
+    /// Bindings with no keys at all, not even the arrows.
+    pub fn empty() -> KeyBindings {
+        KeyBindings {
+            keys: HashMap::new(),
+        }
+    }
+
+    pub fn bind(&mut self, key: Key, action: Action) {
+        self.keys.insert(key, action);
+    }
+
+    /// Removes every key bound to `action`.
+    pub fn unbind(&mut self, action: Action) {
+        self.keys.retain(|_, bound| *bound != action);
+    }
+
+    pub fn action(&self, key: Key) -> Option<Action> {
+        match (self.keys.get(&key), key) {
+            (Some(action), _) => Some(*action),
+            (None, Key::Char(c)) => Some(Action::Command(c)),
+            (None, _) => None,
+        }
+    }
+
+    /// Keys bound to `action`, by name, sorted.
+    pub fn keys(&self, action: Action) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .keys
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(key, _)| key_name(*key))
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let mut bindings = KeyBindings::empty();
+        bindings.bind(Key::Up, Action::MoveUp);
+        bindings.bind(Key::Down, Action::MoveDown);
+        bindings.bind(Key::Left, Action::MoveLeft);
+        bindings.bind(Key::Right, Action::MoveRight);
+        bindings.bind(Key::Esc, Action::Quit);
+        bindings.bind(Key::F(3), Action::ToggleDebug);
+        bindings
+    }
+}
+
+/// Reads a key name: `Up`, `Down`, `Left`, `Right`, `Esc`, `Enter`, `Tab`, `Backspace`,
+/// `Space`, `F1` to `F12` or a single character.
+pub fn parse_key(name: &str) -> Result<Key, String> {
+    let key = match name {
+        "Up" => Key::Up,
+        "Down" => Key::Down,
+        "Left" => Key::Left,
+        "Right" => Key::Right,
+        "Esc" => Key::Esc,
+        "Enter" => Key::Char('\n'),
+        "Tab" => Key::Char('\t'),
+        "Backspace" => Key::Backspace,
+        "Space" => Key::Char(' '),
+        _ => {
+            let function = name
+                .strip_prefix('F')
+                .and_then(|n| n.parse::<u8>().ok())
+                .filter(|n| (1..=12).contains(n));
+            let mut chars = name.chars();
+            match (function, chars.next(), chars.next()) {
+                (Some(n), _, _) => Key::F(n),
+                (None, Some(c), None) => Key::Char(c),
+                _ => return Err(format!("unknown key `{}`", name)),
+            }
+        }
+    };
+    Ok(key)
+}
+
+/// The name `parse_key` reads back as `key`.
+pub fn key_name(key: Key) -> String {
+    match key {
+        Key::Up => String::from("Up"),
+        Key::Down => String::from("Down"),
+        Key::Left => String::from("Left"),
+        Key::Right => String::from("Right"),
+        Key::Esc => String::from("Esc"),
+        Key::Backspace => String::from("Backspace"),
+        Key::F(n) => format!("F{}", n),
+        Key::Char('\n') => String::from("Enter"),
+        Key::Char('\t') => String::from("Tab"),
+        Key::Char(' ') => String::from("Space"),
+        Key::Char(c) => c.to_string(),
+        key => format!("{:?}", key),
+    }
+}
@@ -0,0 +1,25 @@
+//! A small terminal game engine and the classic snake game built on it.
+//!
+//! `game_engine` runs any `GameScene` with its `GameObject`s: it reads the keyboard,
+//! updates the scene on a fixed tick and draws it in the terminal. `game_utils` has the
+//! geometry shared by objects, `ecs` lets a game build things out of entities, components
+//! and systems instead, `timers` sends events once some game time has passed, `animation`
+//! plays frames and tweens on entities and draws text over the board, `collision` finds the
+//! objects that ran into each other and `asciicast` records what gets drawn,
+//! `debug_overlay` measures the game thread for the overlay `F3` shows, `key_bindings` maps
+//! keys to actions and `style` colors what is drawn. The snake game itself lives in
+//! `snake_game`.
+
+extern crate termion;
+
+pub mod animation;
+pub mod asciicast;
+pub mod collision;
+pub mod debug_overlay;
+pub mod ecs;
+pub mod game_engine;
+pub mod game_utils;
+pub mod key_bindings;
+pub mod snake_game;
+pub mod style;
+pub mod timers;
@@ -1,29 +1,428 @@
-mod apple;
-mod game_engine;
-mod game_utils;
-mod snake;
-mod snake_game_scene;
-mod snake_parts;
+mod settings;
 
-extern crate termion;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand};
+use settings::Settings;
+use snake::asciicast::AsciicastWriter;
+use snake::game_engine::{EngineConfig, Game};
+use snake::snake_game::bots;
+use snake::snake_game::editor::EditorScene;
+use snake::snake_game::level::Level;
+use snake::snake_game::replay::{Replay, ReplayScene, ReplayWriter};
+use snake::snake_game::scores::HighScores;
+use snake::snake_game::simulator::{self, OutputFormat, SimulationConfig};
+use snake::snake_game::snake_game_scene::{
+    Respawn, BOARD_HEIGHTS, BOARD_WIDTHS, COUNTDOWN_TIMES, INVULNERABLE_TIMES,
+};
+use snake::snake_game::{Difficulty, SnakeGameConfig, SnakeGameScene};
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+use std::time::Duration;
 
-use crate::game_engine::{Action, Game, GameData};
-use crate::snake_game_scene::SnakeGameScene;
-use std::sync::{mpsc, Arc, Mutex};
+/// Snake in the terminal.
+///
+/// Without a command a new game is played. Preferences are read from
+/// `~/.config/snake/config.toml`, or the file in `SNAKE_CONFIG`, then from `SNAKE_*`
+/// environment variables such as `SNAKE_GAME_WIDTH`, and flags override both.
+#[derive(Parser)]
+#[command(name = "snake", version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    play: PlayArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play a game
+    Play(PlayArgs),
+    /// Watch a recorded game
+    Replay(ReplayArgs),
+    /// Let a bot play many games without a terminal and print statistics
+    Simulate(SimulateArgs),
+    /// Show the best games
+    Scores(ScoresArgs),
+    /// Draw the walls of a level file
+    Editor(EditorArgs),
+    /// Inspect the configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the effective configuration, with these flags applied, as TOML
+    Dump(BoardArgs),
+}
+
+#[derive(Args)]
+struct BoardArgs {
+    /// Board width in cells [config: game.width]
+    #[arg(long, value_parser = in_range(BOARD_WIDTHS))]
+    width: Option<usize>,
+    /// Board height in cells [config: game.height]
+    #[arg(long, value_parser = in_range(BOARD_HEIGHTS))]
+    height: Option<usize>,
+    /// Snake speed and number of lives [config: game.difficulty]
+    #[arg(long, value_parser = difficulty())]
+    difficulty: Option<Difficulty>,
+    /// Leave the board on one edge to come back on the opposite one [config: game.wrap]
+    #[arg(long, overrides_with = "no_wrap")]
+    wrap: bool,
+    /// Die on the edges of the board even if the config wraps
+    #[arg(long, overrides_with = "wrap")]
+    no_wrap: bool,
+    /// Level file with walls to play in
+    #[arg(long, value_name = "FILE")]
+    level: Option<String>,
+}
+
+#[derive(Args)]
+struct PlayArgs {
+    #[command(flatten)]
+    board: BoardArgs,
+    /// Seed for apple placement [default: random]
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Record the game to a replay file
+    #[arg(long, value_name = "FILE")]
+    record: Option<String>,
+    /// Record the screen to an asciicast file
+    #[arg(long, value_name = "FILE")]
+    cast: Option<String>,
+    /// Show the frame rate in the title bar [config: display.show_fps]
+    #[arg(long)]
+    show_fps: bool,
+    /// Color theme, built-in or a theme file [config: display.theme]
+    #[arg(long, value_name = "NAME")]
+    theme: Option<String>,
+    /// Characters to draw with [config: display.glyphs]
+    #[arg(long, value_parser = PossibleValuesParser::new(settings::GLYPH_SETS))]
+    glyphs: Option<String>,
+    /// Countdown before the snake moves, at the start and after losing a life, in milliseconds
+    /// [config: game.respawn_countdown_ms] [default: by difficulty]
+    #[arg(long, value_name = "MS", value_parser = in_range(COUNTDOWN_TIMES))]
+    respawn_countdown_ms: Option<usize>,
+    /// Time the snake can't die for once it moves again, in milliseconds
+    /// [config: game.invulnerable_ms] [default: by difficulty]
+    #[arg(long, value_name = "MS", value_parser = in_range(INVULNERABLE_TIMES))]
+    invulnerable_ms: Option<usize>,
+}
+
+#[derive(Args)]
+struct ReplayArgs {
+    /// Replay file written by `play --record`
+    file: String,
+    /// Record the screen to an asciicast file
+    #[arg(long, value_name = "FILE")]
+    cast: Option<String>,
+    /// Render the whole replay into the `--cast` file without opening the game
+    #[arg(long, requires = "cast")]
+    headless: bool,
+}
+
+#[derive(Args)]
+struct SimulateArgs {
+    /// Number of games to play
+    games: u64,
+    #[command(flatten)]
+    board: BoardArgs,
+    /// Bot that plays the games
+    #[arg(long, default_value = "greedy", value_parser = PossibleValuesParser::new(bots::BOT_NAMES))]
+    bot: String,
+    /// Seed of the first game, game `i` uses `seed + i`
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Worker threads [default: available cores]
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Output format of the statistics
+    #[arg(long, default_value = "csv", value_parser = output_format())]
+    format: OutputFormat,
+}
+
+#[derive(Args)]
+struct ScoresArgs {
+    /// Number of games to show
+    #[arg(long, default_value_t = 10)]
+    limit: usize,
+}
+
+#[derive(Args)]
+struct EditorArgs {
+    /// Level file to edit, created when saving if it doesn't exist
+    file: String,
+    /// Board width in cells [config: game.width]
+    #[arg(long, value_parser = in_range(BOARD_WIDTHS))]
+    width: Option<usize>,
+    /// Board height in cells [config: game.height]
+    #[arg(long, value_parser = in_range(BOARD_HEIGHTS))]
+    height: Option<usize>,
+}
 
 fn main() {
-    let (sender, receiver) = mpsc::channel::<GameData>();
-    let sender = Arc::new(Mutex::new(Some(sender)));
-    let game_scene = SnakeGameScene::new();
+    let cli = Cli::parse();
+    let config_file = std::env::var_os("SNAKE_CONFIG").map(PathBuf::from);
+    let result = Settings::load(config_file.as_deref()).and_then(|settings| match cli.command {
+        Some(Command::Play(args)) => play(args, &settings),
+        Some(Command::Replay(args)) => replay(args, &settings),
+        Some(Command::Simulate(args)) => simulate(args, &settings),
+        Some(Command::Scores(args)) => scores(args),
+        Some(Command::Editor(args)) => editor(args, &settings),
+        Some(Command::Config(ConfigCommand::Dump(args))) => dump_config(args, settings),
+        None => play(cli.play, &settings),
+    });
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
+
+fn play(args: PlayArgs, settings: &Settings) -> Result<(), String> {
+    let config = game_config(&args.board, settings)?;
+    let config = SnakeGameConfig {
+        seed: args.seed.unwrap_or_else(rand::random),
+        scores_file: HighScores::default_path(),
+        respawn: Some(respawn(&args, settings, config.difficulty)),
+        ..config
+    };
+    let recorder = match &args.record {
+        Some(path) => {
+            Some(ReplayWriter::create(path, &config).map_err(|e| format!("{}: {}", path, e))?)
+        }
+        None => None,
+    };
+    let glyphs = Settings::glyph_set(args.glyphs.as_deref().unwrap_or(&settings.glyphs));
+    let engine_config = EngineConfig {
+        show_fps: args.show_fps || settings.show_fps,
+        fps: settings.fps,
+        cell_width: glyphs.cell_width(),
+        ..config.engine_config()
+    };
+    let theme = Settings::load_theme(args.theme.as_deref().unwrap_or(&settings.theme))?;
+    let cast_writer = create_cast_writer(args.cast.as_deref(), engine_config)?;
+    Game::run(Box::new(SnakeGameScene::with_config(config)), |game| {
+        game.set_config(engine_config);
+        game.set_key_bindings(settings.key_bindings());
+        game.set_theme(theme);
+        game.set_glyphs(glyphs);
+        if let Some(recorder) = recorder {
+            game.set_input_recorder(Box::new(recorder));
+        }
+        if let Some(cast_writer) = cast_writer {
+            game.set_cast_writer(cast_writer);
+        }
+    })
+}
+
+fn replay(args: ReplayArgs, settings: &Settings) -> Result<(), String> {
+    let replay = Replay::load(&args.file)?;
+    let glyphs = Settings::glyph_set(&settings.glyphs);
+    let engine_config = EngineConfig {
+        show_fps: settings.show_fps,
+        fps: settings.fps,
+        cell_width: glyphs.cell_width(),
+        ..replay.config.engine_config()
+    };
+    let theme = Settings::load_theme(&settings.theme)?;
+    let cast_writer = create_cast_writer(args.cast.as_deref(), engine_config)?;
+    let scene = Box::new(ReplayScene::new(replay));
+    if args.headless {
+        let cast_writer = cast_writer.ok_or("`--headless` needs `--cast FILE`")?;
+        let mut game = Game::new(scene);
+        game.set_config(engine_config);
+        game.set_theme(theme);
+        game.set_glyphs(glyphs);
+        Game::render_headless(game.simulation(), cast_writer, |simulation| {
+            simulation
+                .with_scene(|scene: &ReplayScene, _| scene.ended())
+                .unwrap_or(true)
+        });
+        return Ok(());
+    }
+    Game::run(scene, |game| {
+        game.set_config(engine_config);
+        game.set_key_bindings(settings.key_bindings());
+        game.set_theme(theme);
+        game.set_glyphs(glyphs);
+        if let Some(cast_writer) = cast_writer {
+            game.set_cast_writer(cast_writer);
+        }
+    })
+}
 
-    let game = Game::new(Box::new(game_scene), Arc::clone(&sender));
-    game.lock().unwrap().start();
+fn simulate(args: SimulateArgs, settings: &Settings) -> Result<(), String> {
+    let config = SimulationConfig {
+        game: game_config(&args.board, settings)?,
+        games: args.games,
+        bot: args.bot,
+        seed: args.seed,
+        threads: args
+            .threads
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get())),
+    };
+    let stats = simulator::simulate(&config)?;
+    println!("{}", stats.format(args.format));
+    Ok(())
+}
+
+fn scores(args: ScoresArgs) -> Result<(), String> {
+    let path = HighScores::default_path().ok_or("can't find the home directory")?;
+    let scores = HighScores::load(&path)?;
+    let top = scores.top(args.limit);
+    if top.is_empty() {
+        println!("No games played yet.");
+        return Ok(());
+    }
+    println!(
+        "{:>3}  {:>6}  {:>6}  {:<10}  {:<7}  {:<5}  {:<10}  level",
+        "#", "score", "apples", "difficulty", "board", "edges", "date"
+    );
+    for (i, entry) in top.iter().enumerate() {
+        println!(
+            "{:>3}  {:>6}  {:>6}  {:<10}  {:<7}  {:<5}  {:<10}  {}",
+            i + 1,
+            entry.score,
+            entry.apples,
+            entry.difficulty.to_string(),
+            format!("{}x{}", entry.width, entry.height),
+            if entry.wrap { "wrap" } else { "walls" },
+            date(entry.timestamp),
+            entry.level
+        );
+    }
+    Ok(())
+}
+
+fn editor(args: EditorArgs, settings: &Settings) -> Result<(), String> {
+    let scene = EditorScene::new(
+        &args.file,
+        args.width.unwrap_or(settings.width),
+        args.height.unwrap_or(settings.height),
+    )?;
+    let glyphs = Settings::glyph_set(&settings.glyphs);
+    let engine_config = EngineConfig {
+        show_fps: settings.show_fps,
+        fps: settings.fps,
+        cell_width: glyphs.cell_width(),
+        ..scene.engine_config()
+    };
+    let theme = Settings::load_theme(&settings.theme)?;
+    Game::run(Box::new(scene), |game| {
+        game.set_config(engine_config);
+        game.set_key_bindings(settings.key_bindings());
+        game.set_theme(theme);
+        game.set_glyphs(glyphs);
+    })
+}
+
+fn dump_config(args: BoardArgs, mut settings: Settings) -> Result<(), String> {
+    let config = game_config(&args, &settings)?;
+    settings.width = config.width;
+    settings.height = config.height;
+    settings.difficulty = config.difficulty;
+    settings.wrap = config.wrap;
+    print!("{}", settings.dump());
+    Ok(())
+}
+
+/// The game configuration shared by `play` and `simulate`: the settings with the command
+/// line flags on top, seeded with 0.
+fn game_config(args: &BoardArgs, settings: &Settings) -> Result<SnakeGameConfig, String> {
+    let defaults = settings.game_config();
+    let level = match &args.level {
+        Some(path) => Some(Level::load(path)?),
+        None => None,
+    };
+    let config = SnakeGameConfig {
+        width: args.width.unwrap_or(defaults.width),
+        height: args.height.unwrap_or(defaults.height),
+        difficulty: args.difficulty.unwrap_or(defaults.difficulty),
+        wrap: (defaults.wrap || args.wrap) && !args.no_wrap,
+        level,
+        ..defaults
+    };
+    config.validate().map_err(|e| match &args.level {
+        Some(path) => format!("{}: {}", path, e),
+        None => e,
+    })?;
+    Ok(config)
+}
+
+fn create_cast_writer(
+    path: Option<&str>,
+    config: EngineConfig,
+) -> Result<Option<AsciicastWriter>, String> {
+    match path {
+        Some(path) => {
+            AsciicastWriter::create(path, config.terminal_width(), config.terminal_height())
+                .map(Some)
+                .map_err(|e| format!("{}: {}", path, e))
+        }
+        None => Ok(None),
+    }
+}
+
+/// The respawn times of the flags, or else of the settings.
+fn respawn(args: &PlayArgs, settings: &Settings, difficulty: Difficulty) -> Respawn {
+    let mut respawn = settings.respawn(difficulty);
+    if let Some(ms) = args.respawn_countdown_ms {
+        respawn.countdown = Duration::from_millis(ms as u64);
+    }
+    if let Some(ms) = args.invulnerable_ms {
+        respawn.invulnerable = Duration::from_millis(ms as u64);
+    }
+    respawn
+}
 
-    loop {
-        if let Ok(game_data) = receiver.recv() {
-            if let Action::Quit = game_data.action {
-                break;
-            }
-        };
+fn in_range(range: RangeInclusive<usize>) -> impl Fn(&str) -> Result<usize, String> + Clone {
+    move |value| {
+        let size: usize = value
+            .parse()
+            .map_err(|_| format!("`{}` isn't a number", value))?;
+        if !range.contains(&size) {
+            return Err(format!(
+                "{} is out of range, expected {} to {}",
+                size,
+                range.start(),
+                range.end()
+            ));
+        }
+        Ok(size)
     }
 }
+
+fn difficulty() -> impl TypedValueParser<Value = Difficulty> {
+    PossibleValuesParser::new(["easy", "normal", "hard"])
+        .map(|value| value.parse::<Difficulty>().unwrap())
+}
+
+fn output_format() -> impl TypedValueParser<Value = OutputFormat> {
+    PossibleValuesParser::new(["csv", "json"]).map(|value| match value.as_str() {
+        "json" => OutputFormat::Json,
+        _ => OutputFormat::Csv,
+    })
+}
+
+/// `YYYY-MM-DD` in UTC for seconds since the Unix epoch.
+fn date(timestamp: u64) -> String {
+    // Civil-from-days, see http://howardhinnant.github.io/date_algorithms.html
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
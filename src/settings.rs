@@ -0,0 +1,507 @@
+use snake::game_engine::{self, Action};
+use snake::key_bindings::{self, KeyBindings};
+use snake::snake_game::snake_game_scene::{
+    Respawn, BOARD_HEIGHTS, BOARD_WIDTHS, COUNTDOWN_TIMES, INVULNERABLE_TIMES,
+};
+use snake::snake_game::{glyph_sets, themes, Difficulty, SnakeGameConfig};
+use snake::style::{GlyphSet, Theme};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// `auto` picks a glyph set from the locale.
+pub const GLYPH_SETS: [&str; 4] = ["auto", "emoji", "box", "ascii"];
+
+/// Actions that can be bound to keys, by their name under `[keys]`.
+const KEY_ACTIONS: [(&str, Action); 7] = [
+    ("up", Action::MoveUp),
+    ("down", Action::MoveDown),
+    ("left", Action::MoveLeft),
+    ("right", Action::MoveRight),
+    ("quit", Action::Quit),
+    ("restart", Action::Restart),
+    ("debug", Action::ToggleDebug),
+];
+
+/// Every setting, as `section.name`, in the order `dump` prints them.
+const KEYS: [&str; 18] = [
+    "game.difficulty",
+    "game.width",
+    "game.height",
+    "game.wrap",
+    "game.tick_rate",
+    "game.respawn_countdown_ms",
+    "game.invulnerable_ms",
+    "display.theme",
+    "display.glyphs",
+    "display.show_fps",
+    "display.fps",
+    "keys.up",
+    "keys.down",
+    "keys.left",
+    "keys.right",
+    "keys.quit",
+    "keys.restart",
+    "keys.debug",
+];
+
+/// Persistent preferences, resolved from the defaults, the config file and `SNAKE_*`
+/// environment variables, in that order. Command-line flags are applied on top by `main`.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub difficulty: Difficulty,
+    pub width: usize,
+    pub height: usize,
+    pub wrap: bool,
+    pub tick_rate: usize,
+    /// Respawn times, the difficulty's unless set.
+    pub respawn_countdown_ms: Option<usize>,
+    pub invulnerable_ms: Option<usize>,
+    pub theme: String,
+    pub glyphs: String,
+    pub show_fps: bool,
+    pub fps: usize,
+    /// Key names bound to each entry of `KEY_ACTIONS`.
+    keys: HashMap<&'static str, Vec<String>>,
+}
+
+/// A setting's value as found in one of the layers.
+enum Value<'a> {
+    Toml(&'a toml::Value),
+    Env(&'a str),
+}
+
+impl Settings {
+    /// `$XDG_CONFIG_HOME/snake`, or `~/.config/snake`.
+    fn config_dir() -> Option<PathBuf> {
+        let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(config_home.join("snake"))
+    }
+
+    /// `$XDG_CONFIG_HOME/snake/config.toml`, or `~/.config/snake/config.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        Some(Self::config_dir()?.join("config.toml"))
+    }
+
+    /// The glyph set called `name`, one of `GLYPH_SETS`.
+    pub fn glyph_set(name: &str) -> GlyphSet {
+        match name {
+            "auto" => glyph_sets::detect(),
+            name => glyph_sets::glyph_set(name).unwrap(),
+        }
+    }
+
+    /// Finds the theme called `name`: a built-in one, `name.toml` in the `themes` directory
+    /// next to the config file, or the theme file at the path `name`.
+    pub fn load_theme(name: &str) -> Result<Theme, String> {
+        if let Some(theme) = themes::builtin(name) {
+            return Ok(theme);
+        }
+        let installed = Self::config_dir()
+            .map(|dir| dir.join("themes").join(format!("{}.toml", name)))
+            .filter(|path| path.exists());
+        match installed {
+            Some(path) => themes::load(&path.to_string_lossy()),
+            None if Path::new(name).exists() => themes::load(name),
+            None => {
+                let names: Vec<&str> = themes::BUILTIN.iter().map(|(name, _)| *name).collect();
+                Err(format!(
+                    "unknown theme `{}`, expected a theme file or one of: {}",
+                    name,
+                    names.join(", ")
+                ))
+            }
+        }
+    }
+
+    /// Resolves the settings from `path`, which has to exist, or from the default config
+    /// file if there is one, and then from the environment.
+    pub fn load(path: Option<&Path>) -> Result<Settings, String> {
+        let mut settings = Settings::default();
+        let path = match path {
+            Some(path) => Some(path.to_path_buf()),
+            None => Self::default_path().filter(|path| path.exists()),
+        };
+        if let Some(path) = path {
+            let text =
+                std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            settings
+                .apply_file(&text)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+        settings.apply_env()?;
+        settings.check_keys()?;
+        Ok(settings)
+    }
+
+    /// The game configuration these settings describe, seeded with 0.
+    pub fn game_config(&self) -> SnakeGameConfig {
+        SnakeGameConfig {
+            width: self.width,
+            height: self.height,
+            difficulty: self.difficulty,
+            wrap: self.wrap,
+            tick_rate: self.tick_rate,
+            ..SnakeGameConfig::default()
+        }
+    }
+
+    /// How the snake gets back into a game at `difficulty`, with the times that are set.
+    pub fn respawn(&self, difficulty: Difficulty) -> Respawn {
+        let defaults = difficulty.respawn();
+        let millis = |ms: usize| Duration::from_millis(ms as u64);
+        Respawn {
+            countdown: self.respawn_countdown_ms.map_or(defaults.countdown, millis),
+            invulnerable: self.invulnerable_ms.map_or(defaults.invulnerable, millis),
+        }
+    }
+
+    pub fn key_bindings(&self) -> KeyBindings {
+        let mut bindings = KeyBindings::empty();
+        for (name, action) in KEY_ACTIONS {
+            for key in &self.keys[name] {
+                bindings.bind(key_bindings::parse_key(key).unwrap(), action);
+            }
+        }
+        bindings
+    }
+
+    /// The effective settings as a config file. Respawn times that aren't set are commented
+    /// out, so they keep following the difficulty.
+    pub fn dump(&self) -> String {
+        let mut text = String::new();
+        let mut section = "";
+        for key in KEYS {
+            let (key_section, name) = key.split_once('.').unwrap();
+            if key_section != section {
+                if !section.is_empty() {
+                    text.push('\n');
+                }
+                text.push_str(&format!("[{}]\n", key_section));
+                section = key_section;
+            }
+            let unset = match key {
+                "game.respawn_countdown_ms" => self.respawn_countdown_ms.is_none(),
+                "game.invulnerable_ms" => self.invulnerable_ms.is_none(),
+                _ => false,
+            };
+            let comment = if unset { "# " } else { "" };
+            text.push_str(&format!("{}{} = {}\n", comment, name, self.get(key)));
+        }
+        text
+    }
+
+    fn apply_file(&mut self, text: &str) -> Result<(), String> {
+        let table: toml::Table = text.parse().map_err(|e: toml::de::Error| {
+            let line = e
+                .span()
+                .map_or(1, |span| text[..span.start].matches('\n').count() + 1);
+            format!(
+                "line {}: {}",
+                line,
+                e.message().trim_end().replace('\n', ", ")
+            )
+        })?;
+        for (section, values) in &table {
+            let values = match values.as_table() {
+                Some(values) => values,
+                None => return Err(format!("unknown key `{}`", section)),
+            };
+            for (name, value) in values {
+                let key = format!("{}.{}", section, name);
+                self.set(&key, Value::Toml(value))
+                    .map_err(|e| format!("`{}`: {}", key, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads `SNAKE_GAME_WIDTH` for `game.width` and so on. List values are comma-separated.
+    fn apply_env(&mut self) -> Result<(), String> {
+        for key in KEYS {
+            let variable = format!("SNAKE_{}", key.replace('.', "_").to_uppercase());
+            if let Ok(value) = std::env::var(&variable) {
+                self.set(key, Value::Env(&value))
+                    .map_err(|e| format!("environment variable `{}`: {}", variable, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set(&mut self, key: &str, value: Value) -> Result<(), String> {
+        match key {
+            "game.difficulty" => self.difficulty = value.string()?.parse()?,
+            "game.width" => self.width = value.integer(BOARD_WIDTHS)?,
+            "game.height" => self.height = value.integer(BOARD_HEIGHTS)?,
+            "game.wrap" => self.wrap = value.boolean()?,
+            "game.tick_rate" => self.tick_rate = value.integer(game_engine::TICK_RATES)?,
+            "game.respawn_countdown_ms" => {
+                self.respawn_countdown_ms = Some(value.integer(COUNTDOWN_TIMES)?)
+            }
+            "game.invulnerable_ms" => {
+                self.invulnerable_ms = Some(value.integer(INVULNERABLE_TIMES)?)
+            }
+            "display.theme" => {
+                let name = value.string()?;
+                Self::load_theme(name)?;
+                self.theme = name.to_string();
+            }
+            "display.glyphs" => self.glyphs = value.one_of(&GLYPH_SETS)?,
+            "display.show_fps" => self.show_fps = value.boolean()?,
+            "display.fps" => self.fps = value.integer(game_engine::FPS_RATES)?,
+            _ => {
+                let action = key
+                    .strip_prefix("keys.")
+                    .and_then(|name| KEY_ACTIONS.iter().find(|(action, _)| *action == name))
+                    .map(|(action, _)| *action)
+                    .ok_or_else(|| String::from("unknown key"))?;
+                let keys = value
+                    .list()?
+                    .iter()
+                    .map(|key| key_bindings::parse_key(key).map(key_bindings::key_name))
+                    .collect::<Result<_, _>>()?;
+                self.keys.insert(action, keys);
+            }
+        }
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> toml::Value {
+        match key {
+            "game.difficulty" => toml::Value::from(self.difficulty.to_string()),
+            "game.width" => toml::Value::from(self.width as i64),
+            "game.height" => toml::Value::from(self.height as i64),
+            "game.wrap" => toml::Value::from(self.wrap),
+            "game.tick_rate" => toml::Value::from(self.tick_rate as i64),
+            "game.respawn_countdown_ms" => {
+                toml::Value::from(self.respawn(self.difficulty).countdown.as_millis() as i64)
+            }
+            "game.invulnerable_ms" => {
+                toml::Value::from(self.respawn(self.difficulty).invulnerable.as_millis() as i64)
+            }
+            "display.theme" => toml::Value::from(self.theme.clone()),
+            "display.glyphs" => toml::Value::from(self.glyphs.clone()),
+            "display.show_fps" => toml::Value::from(self.show_fps),
+            "display.fps" => toml::Value::from(self.fps as i64),
+            _ => toml::Value::from(self.keys[key.trim_start_matches("keys.")].clone()),
+        }
+    }
+
+    /// Fails if a key is bound to two actions.
+    fn check_keys(&self) -> Result<(), String> {
+        let mut bound: HashMap<&str, &str> = HashMap::new();
+        for (action, _) in KEY_ACTIONS {
+            for key in &self.keys[action] {
+                if let Some(other) = bound.insert(key, action) {
+                    return Err(format!(
+                        "key `{}` is bound to both `keys.{}` and `keys.{}`",
+                        key, other, action
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let game = SnakeGameConfig::default();
+        let mut bindings = KeyBindings::default();
+        bindings.bind(termion::event::Key::Char('r'), Action::Restart);
+        Settings {
+            difficulty: game.difficulty,
+            width: game.width,
+            height: game.height,
+            wrap: game.wrap,
+            tick_rate: game.tick_rate,
+            respawn_countdown_ms: None,
+            invulnerable_ms: None,
+            theme: String::from(themes::BUILTIN[0].0),
+            glyphs: String::from(GLYPH_SETS[0]),
+            show_fps: false,
+            fps: game_engine::DEFAULT_FPS,
+            keys: KEY_ACTIONS
+                .iter()
+                .map(|(name, action)| (*name, bindings.keys(*action)))
+                .collect(),
+        }
+    }
+}
+
+impl Value<'_> {
+    fn string(&self) -> Result<&str, String> {
+        match self {
+            Value::Toml(toml::Value::String(s)) => Ok(s),
+            Value::Env(s) => Ok(s),
+            Value::Toml(value) => Err(format!("expected a string, found {}", value)),
+        }
+    }
+
+    fn one_of(&self, names: &[&str]) -> Result<String, String> {
+        let name = self.string()?;
+        if names.contains(&name) {
+            Ok(name.to_string())
+        } else {
+            Err(format!(
+                "unknown name `{}`, expected one of: {}",
+                name,
+                names.join(", ")
+            ))
+        }
+    }
+
+    fn integer(&self, range: std::ops::RangeInclusive<usize>) -> Result<usize, String> {
+        let expected = || {
+            format!(
+                "expected an integer from {} to {}",
+                range.start(),
+                range.end()
+            )
+        };
+        let value = match self {
+            Value::Toml(toml::Value::Integer(n)) => usize::try_from(*n).ok(),
+            Value::Env(s) => s.parse().ok(),
+            Value::Toml(_) => None,
+        };
+        match value {
+            Some(n) if range.contains(&n) => Ok(n),
+            _ => Err(format!("{}, found {}", expected(), self)),
+        }
+    }
+
+    fn boolean(&self) -> Result<bool, String> {
+        match self {
+            Value::Toml(toml::Value::Boolean(b)) => Ok(*b),
+            Value::Env("true" | "1") => Ok(true),
+            Value::Env("false" | "0") => Ok(false),
+            _ => Err(format!("expected true or false, found {}", self)),
+        }
+    }
+
+    fn list(&self) -> Result<Vec<String>, String> {
+        match self {
+            Value::Toml(toml::Value::Array(values)) => values
+                .iter()
+                .map(|value| match value {
+                    toml::Value::String(s) => Ok(s.clone()),
+                    value => Err(format!("expected key names, found {}", value)),
+                })
+                .collect(),
+            Value::Toml(toml::Value::String(s)) => Ok(vec![s.clone()]),
+            Value::Env(s) => Ok(s.split(',').map(|key| key.trim().to_string()).collect()),
+            Value::Toml(value) => Err(format!("expected key names, found {}", value)),
+        }
+    }
+}
+
+impl std::fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Toml(value) => write!(f, "{}", value),
+            Value::Env(s) => write!(f, "`{}`", s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use termion::event::Key;
+
+    fn from_file(text: &str) -> Result<Settings, String> {
+        let mut settings = Settings::default();
+        settings.apply_file(text)?;
+        settings.check_keys()?;
+        Ok(settings)
+    }
+
+    #[test]
+    fn files_set_what_they_name() {
+        let settings = from_file(
+            "[game]\ndifficulty = \"hard\"\nwidth = 40\nrespawn_countdown_ms = 1200\n\n\
+             [display]\nshow_fps = true\n\n[keys]\nup = [\"Up\", \"k\"]\nquit = \"q\"\n",
+        )
+        .unwrap();
+        assert_eq!(settings.difficulty, Difficulty::Hard);
+        assert_eq!(
+            (settings.width, settings.height),
+            (40, Settings::default().height)
+        );
+        assert!(settings.show_fps);
+        assert_eq!(settings.keys["up"], ["Up", "k"]);
+        assert_eq!(settings.keys["quit"], ["q"]);
+        let respawn = settings.respawn(settings.difficulty);
+        assert_eq!(respawn.countdown, Duration::from_millis(1200));
+        assert_eq!(
+            respawn.invulnerable,
+            Difficulty::Hard.respawn().invulnerable
+        );
+    }
+
+    #[test]
+    fn rebound_keys_no_longer_do_what_they_did() {
+        let bindings = from_file("[keys]\nrestart = \"n\"\n")
+            .unwrap()
+            .key_bindings();
+        assert_eq!(bindings.action(Key::Char('n')), Some(Action::Restart));
+        assert_eq!(bindings.action(Key::Char('r')), Some(Action::Command('r')));
+        let bindings = Settings::default().key_bindings();
+        assert_eq!(bindings.action(Key::Char('r')), Some(Action::Restart));
+    }
+
+    #[test]
+    fn dumped_settings_read_back_the_same() {
+        let mut settings =
+            from_file("[game]\ninvulnerable_ms = 0\n[keys]\ndebug = \"F5\"").unwrap();
+        settings.set("display.glyphs", Value::Env("box")).unwrap();
+        let dump = settings.dump();
+        assert!(dump.contains("\n# respawn_countdown_ms = 3000\ninvulnerable_ms = 0\n"));
+        let read = from_file(&dump).unwrap();
+        assert_eq!(read.dump(), dump);
+        assert_eq!(read.respawn_countdown_ms, None);
+        assert_eq!(Settings::default().dump(), from_file("").unwrap().dump());
+    }
+
+    #[test]
+    fn bad_settings_say_where_they_are() {
+        let error = |text: &str| from_file(text).unwrap_err();
+        assert_eq!(
+            error("[game]\nwidth = 5"),
+            "`game.width`: expected an integer from 20 to 200, found 5"
+        );
+        assert_eq!(error("[game]\nspeed = 3"), "`game.speed`: unknown key");
+        assert_eq!(error("difficulty = \"hard\""), "unknown key `difficulty`");
+        assert_eq!(
+            error("[game]\ndifficulty = \"brutal\""),
+            "`game.difficulty`: unknown difficulty `brutal`, expected easy, normal or hard"
+        );
+        assert_eq!(
+            error("[display]\nglyphs = \"runes\""),
+            "`display.glyphs`: unknown name `runes`, expected one of: auto, emoji, box, ascii"
+        );
+        assert_eq!(
+            error("[keys]\nup = [\"Hyper\"]"),
+            "`keys.up`: unknown key `Hyper`"
+        );
+        assert_eq!(
+            error("[keys]\nup = [1]"),
+            "`keys.up`: expected key names, found 1"
+        );
+        assert_eq!(
+            error("[keys]\nrestart = \"q\"\nquit = \"q\""),
+            "key `q` is bound to both `keys.quit` and `keys.restart`"
+        );
+        assert!(error("[game]\n\nwidth = ").starts_with("line 3: "));
+        let mut settings = Settings::default();
+        assert_eq!(
+            settings.set("display.show_fps", Value::Env("maybe")),
+            Err(String::from("expected true or false, found `maybe`"))
+        );
+        assert_eq!(settings.set("keys.left", Value::Env("h, Left")), Ok(()));
+        assert_eq!(settings.keys["left"], ["h", "Left"]);
+    }
+}
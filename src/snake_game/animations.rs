@@ -0,0 +1,118 @@
+use crate::animation::{Animation, Easing, Frame, Label, Motion, Tween};
+use crate::ecs::{Entity, Glyph, Lifetime, Position};
+use crate::game_engine::DrawLayer;
+use crate::game_utils::Direction;
+use crate::snake_game::board::Board;
+use crate::snake_game::snake_game_scene::SnakeWorld;
+use std::time::Duration;
+
+/// Time each frame of the flash on an eaten apple is shown.
+const FLASH_FRAME: Duration = Duration::from_millis(60);
+/// Time the points for an apple take to float up from the row above it, and the cells
+/// they rise.
+const POINTS_RISE: Duration = Duration::from_millis(600);
+const POINTS_HEIGHT: f64 = 2.0;
+/// Time each part of a dead snake takes to crumble, and between two parts starting to.
+const CRUMBLE_FRAME: Duration = Duration::from_millis(80);
+const CRUMBLE_STEP: Duration = Duration::from_millis(40);
+/// How long a blink of the snake takes.
+const BLINK_PERIOD: Duration = Duration::from_millis(200);
+/// How long a blink of the text asking for a direction takes.
+const HINT_BLINK_PERIOD: Duration = Duration::from_millis(800);
+
+/// Flashes the cell of an eaten apple and floats the `points` it was worth up from it.
+pub fn flash(world: &mut SnakeWorld, position: Position, points: u32) {
+    let flash = world.spawn();
+    let frames = vec![
+        Frame::Shown,
+        Frame::Glyph("flash_fade"),
+        Frame::Shown,
+        Frame::Glyph("flash_fade"),
+    ];
+    let components = world.components_mut();
+    components.insert(flash, position);
+    components.insert(flash, Glyph::new("flash", DrawLayer::Effects));
+    components.insert(flash, Animation::new(frames, FLASH_FRAME).then_despawn());
+    if points == 0 {
+        return;
+    }
+    let label = world.spawn();
+    let (from, to) = (
+        position + Direction::up() * 1.0,
+        position + Direction::up() * (1.0 + POINTS_HEIGHT),
+    );
+    let tween = Tween::new(0.0, 1.0, POINTS_RISE).easing(Easing::EaseOut);
+    let components = world.components_mut();
+    components.insert(label, from);
+    components.insert(
+        label,
+        Label::new(format!("+{}", points), "flash", DrawLayer::Overlay),
+    );
+    components.insert(label, Motion::new(from, to, tween));
+    components.insert(label, Lifetime(POINTS_RISE));
+}
+
+/// Leaves the parts of a snake that lost a life behind, as they were drawn, to crumble to
+/// dust one after the other from the head.
+pub fn crumble(world: &mut SnakeWorld, parts: &[(Position, Glyph)]) {
+    let frames = vec![Frame::Glyph("crumble"), Frame::Glyph("dust")];
+    for (index, (position, glyph)) in parts.iter().enumerate() {
+        let part = world.spawn();
+        let animation = Animation::new(frames.clone(), CRUMBLE_FRAME)
+            .delayed(CRUMBLE_STEP * index as u32)
+            .then_despawn();
+        let components = world.components_mut();
+        components.insert(part, *position);
+        components.insert(
+            part,
+            Glyph {
+                layer: DrawLayer::Effects,
+                ..*glyph
+            },
+        );
+        components.insert(part, animation);
+    }
+}
+
+/// Makes the parts of a snake blink for `duration`, e.g. while it can't die.
+pub fn blink(world: &mut SnakeWorld, parts: &[Entity], duration: Duration) {
+    let times = duration.as_millis().div_ceil(BLINK_PERIOD.as_millis()) as u32;
+    for part in parts {
+        let animation = Animation::blink(BLINK_PERIOD, times);
+        world.components_mut().insert(*part, animation);
+    }
+}
+
+/// Counts 3, 2, 1 over `duration` above the board's start cell. With `go`, then shows `GO!`
+/// for as long as each number and goes away, otherwise stays blank until it is despawned.
+pub fn countdown(world: &mut SnakeWorld, board: &Board, duration: Duration, go: bool) -> Entity {
+    let (x, y) = board.start();
+    let mut frames = vec![Frame::Text("3"), Frame::Text("2"), Frame::Text("1")];
+    let mut animation = Animation::new(frames.clone(), duration / 3);
+    if go {
+        frames.push(Frame::Text("GO!"));
+        animation = Animation::new(frames, duration / 3).then_despawn();
+    }
+    let countdown = world.spawn();
+    let components = world.components_mut();
+    components.insert(
+        countdown,
+        Position {
+            x: x as f64,
+            y: y.saturating_sub(2).max(1) as f64,
+        },
+    );
+    components.insert(countdown, Label::new("", "countdown", DrawLayer::Overlay));
+    components.insert(countdown, animation);
+    countdown
+}
+
+/// Turns a countdown that is over into blinking text asking for a direction.
+pub fn wait_for_direction(world: &mut SnakeWorld, countdown: Entity) {
+    let components = world.components_mut();
+    components.insert(
+        countdown,
+        Label::new("Press a direction", "countdown", DrawLayer::Overlay),
+    );
+    components.insert(countdown, Animation::blink(HINT_BLINK_PERIOD, 1).looped());
+}
@@ -0,0 +1,122 @@
+use crate::game_engine;
+use crate::game_utils::Position;
+use crate::snake_game::level::Level;
+use rand::Rng;
+use std::collections::HashSet;
+
+/// The playing field shared by the scene and its objects: its size, its walls and whether
+/// leaving one edge brings the snake back on the opposite one.
+///
+/// Cells are numbered from 1, like screen coordinates inside the game area.
+pub struct Board {
+    width: usize,
+    height: usize,
+    wrap: bool,
+    walls: HashSet<(usize, usize)>,
+}
+
+impl Board {
+    pub fn new(width: usize, height: usize, wrap: bool, level: Option<&Level>) -> Board {
+        let walls = match level {
+            Some(level) => level.walls.iter().copied().collect(),
+            None => HashSet::new(),
+        };
+        Board {
+            width,
+            height,
+            wrap,
+            walls,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn wraps(&self) -> bool {
+        self.wrap
+    }
+
+    pub fn walls(&self) -> impl Iterator<Item = &(usize, usize)> {
+        self.walls.iter()
+    }
+
+    /// Cell where the snake's head starts.
+    pub fn start(&self) -> (usize, usize) {
+        (self.width / 2, self.height / 2)
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        (1..=self.width).contains(&x) && (1..=self.height).contains(&y)
+    }
+
+    pub fn is_wall(&self, cell: (usize, usize)) -> bool {
+        self.walls.contains(&cell)
+    }
+
+    /// Whether a snake's head at `position` dies: it is outside the board or on a wall.
+    pub fn blocks(&self, position: Position) -> bool {
+        let cell = self.cell(position);
+        !self.contains(cell) || self.is_wall(cell)
+    }
+
+    /// Screen cell of `position`, taking coordinates left of or above the board into account.
+    pub fn cell(&self, position: Position) -> (usize, usize) {
+        if position.x < 0.5 || position.y < 0.5 {
+            return (0, 0);
+        }
+        position.get_screen_coordinates()
+    }
+
+    /// Brings a position that left the board back on the opposite edge when the board wraps.
+    pub fn wrap(&self, position: Position) -> Position {
+        if !self.wrap {
+            return position;
+        }
+        let wrap = |value: f64, size: usize| {
+            let size = size as f64;
+            if value < 0.5 {
+                value + size
+            } else if value >= size + 0.5 {
+                value - size
+            } else {
+                value
+            }
+        };
+        Position {
+            x: wrap(position.x, self.width),
+            y: wrap(position.y, self.height),
+        }
+    }
+
+    /// Picks a random cell that isn't a wall.
+    pub fn random_free_cell(&self, rng: &mut impl Rng) -> Position {
+        loop {
+            let cell = (
+                rng.gen_range(1..=self.width),
+                rng.gen_range(1..=self.height),
+            );
+            if !self.is_wall(cell) {
+                return Position {
+                    x: cell.0 as f64,
+                    y: cell.1 as f64,
+                };
+            }
+        }
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board::new(
+            game_engine::GAME_AREA_WIDTH,
+            game_engine::GAME_AREA_HEIGHT,
+            false,
+            None,
+        )
+    }
+}
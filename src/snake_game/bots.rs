@@ -0,0 +1,71 @@
+use crate::game_engine::Action;
+use crate::snake_game::snake_env::{Observation, ACTIONS};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Names accepted by `bot_by_name`.
+pub const BOT_NAMES: [&str; 2] = ["random", "greedy"];
+
+/// An agent that plays from `ObservationKind::Features` observations.
+pub trait Bot {
+    fn act(&mut self, observation: &Observation) -> Action;
+}
+
+pub fn bot_by_name(name: &str, seed: u64) -> Option<Box<dyn Bot + Send>> {
+    match name {
+        "random" => Some(Box::new(RandomBot::new(seed))),
+        "greedy" => Some(Box::new(GreedyBot)),
+        _ => None,
+    }
+}
+
+/// Presses a random arrow key on every step.
+pub struct RandomBot {
+    rng: StdRng,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> RandomBot {
+        RandomBot {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Bot for RandomBot {
+    fn act(&mut self, _observation: &Observation) -> Action {
+        ACTIONS[self.rng.gen_range(0..ACTIONS.len())]
+    }
+}
+
+/// Heads for the apple, turning away from walls and its own body when it has to.
+pub struct GreedyBot;
+
+impl Bot for GreedyBot {
+    fn act(&mut self, observation: &Observation) -> Action {
+        let features = match observation {
+            Observation::Features(features) => features,
+            _ => panic!("GreedyBot needs feature observations"),
+        };
+        let is_set = |i: usize| features[i] > 0.5;
+        let heading = [
+            Action::MoveUp,
+            Action::MoveDown,
+            Action::MoveLeft,
+            Action::MoveRight,
+        ];
+        let current = (3..7).find(|i| is_set(*i)).map_or(3, |i| i - 3);
+        // Left and right of up, down, left and right respectively
+        let sides = [(2, 3), (3, 2), (1, 0), (0, 1)];
+        let (left, right) = sides[current];
+
+        // Candidates as (heading index, blocked), straight ahead first
+        let candidates = [(current, is_set(0)), (left, is_set(1)), (right, is_set(2))];
+        let towards_apple = |i: usize| is_set(7 + i);
+        candidates
+            .iter()
+            .find(|(i, blocked)| !blocked && towards_apple(*i))
+            .or_else(|| candidates.iter().find(|(_, blocked)| !blocked))
+            .map_or(heading[current], |(i, _)| heading[*i])
+    }
+}
@@ -0,0 +1,214 @@
+use crate::game_engine::{
+    Action, Draw, EngineConfig, Game, GameData, GameObject, GameScene, GameScreen, ObjectId, Update,
+};
+use crate::game_utils::Position;
+use crate::snake_game::board::Board;
+use crate::snake_game::level::Level;
+use crate::snake_game::snake_game_scene::{SnakeGameSceneActions, SnakeWorld};
+use std::any::Any;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::Duration;
+
+/// The level being edited and the cursor that edits it.
+pub struct EditorGrid {
+    width: usize,
+    height: usize,
+    start: (usize, usize),
+    cursor: (usize, usize),
+    walls: BTreeSet<(usize, usize)>,
+}
+
+impl EditorGrid {
+    fn new(width: usize, height: usize, level: &Level) -> EditorGrid {
+        let start = Board::new(width, height, false, None).start();
+        EditorGrid {
+            width,
+            height,
+            start,
+            cursor: start,
+            walls: level.walls.iter().copied().collect(),
+        }
+    }
+
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    pub fn walls(&self) -> usize {
+        self.walls.len()
+    }
+
+    /// Adds a wall under the cursor, or removes it. The snake's start stays free.
+    fn toggle(&mut self) {
+        if self.cursor == self.start {
+            return;
+        }
+        if !self.walls.remove(&self.cursor) {
+            self.walls.insert(self.cursor);
+        }
+    }
+}
+
+impl Draw for EditorGrid {
+    fn draw(&self, screen: &mut GameScreen) {
+        let (glyph, style) = (screen.glyph("wall"), screen.style("walls"));
+        for (x, y) in &self.walls {
+            Game::<SnakeGameSceneActions>::draw_styled_point(screen, &glyph, style, *x, *y);
+        }
+        let (x, y) = self.start;
+        let start = screen.glyph("start");
+        Game::<SnakeGameSceneActions>::draw_point(screen, &start, x, y);
+        let (x, y) = self.cursor;
+        let cursor = if self.walls.contains(&self.cursor) {
+            screen.glyph("cursor_wall")
+        } else {
+            screen.glyph("cursor")
+        };
+        Game::<SnakeGameSceneActions>::draw_point(screen, &cursor, x, y);
+    }
+    fn get_position(&self) -> Position {
+        Position {
+            x: self.cursor.0 as f64,
+            y: self.cursor.1 as f64,
+        }
+    }
+}
+
+impl Update for EditorGrid {
+    fn update(&mut self, _time_since_last_call: Duration) {}
+}
+
+impl GameObject for EditorGrid {
+    type Item = SnakeGameSceneActions;
+
+    fn action(&mut self, action: Action) {
+        let (x, y) = self.cursor;
+        self.cursor = match action {
+            Action::MoveUp if y > 1 => (x, y - 1),
+            Action::MoveDown if y < self.height => (x, y + 1),
+            Action::MoveLeft if x > 1 => (x - 1, y),
+            Action::MoveRight if x < self.width => (x + 1, y),
+            Action::Command(' ') => {
+                self.toggle();
+                return;
+            }
+            _ => return,
+        };
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Draws walls for a level file: the cursor is moved with the arrows and `Space` toggles a
+/// wall, `S` saves the file. The level's hazards are kept as they are.
+pub struct EditorScene {
+    path: String,
+    name: String,
+    width: usize,
+    height: usize,
+    /// The level as it was opened.
+    level: Level,
+    /// Set once the scene is loaded.
+    grid: Option<ObjectId>,
+    message: String,
+}
+
+impl EditorScene {
+    /// Opens the level at `path` on a `width` x `height` board, or starts an empty one if
+    /// the file doesn't exist yet.
+    pub fn new(path: &str, width: usize, height: usize) -> Result<EditorScene, String> {
+        let level = if Path::new(path).exists() {
+            let level = Level::load(path)?;
+            let start = Board::new(width, height, false, None).start();
+            level
+                .validate(width, height, start)
+                .map_err(|e| format!("{}: {}", path, e))?;
+            level
+        } else {
+            Level::default()
+        };
+        let name = match level.name.as_str() {
+            "" => Path::new(path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            name => name.to_string(),
+        };
+        Ok(EditorScene {
+            path: path.to_string(),
+            name,
+            width,
+            height,
+            level,
+            grid: None,
+            message: String::new(),
+        })
+    }
+
+    pub fn engine_config(&self) -> EngineConfig {
+        EngineConfig {
+            board_width: self.width,
+            board_height: self.height,
+            ..EngineConfig::default()
+        }
+    }
+
+    fn grid<'a>(&self, world: &'a SnakeWorld) -> &'a EditorGrid {
+        self.grid.and_then(|grid| world.get(grid)).expect("No grid")
+    }
+
+    fn save(&mut self, world: &SnakeWorld) {
+        let level = Level {
+            name: self.name.clone(),
+            walls: self.grid(world).walls.iter().copied().collect(),
+            hazards: self.level.hazards.clone(),
+        };
+        self.message = match level.save(&self.path, self.width, self.height) {
+            Ok(()) => format!("Saved {}", self.path),
+            Err(error) => error,
+        };
+    }
+}
+
+impl GameScene<SnakeGameSceneActions> for EditorScene {
+    fn load(&mut self, world: &mut SnakeWorld) {
+        let grid = EditorGrid::new(self.width, self.height, &self.level);
+        self.grid = Some(world.add_object(Box::new(grid)));
+    }
+    fn update(&mut self, _world: &mut SnakeWorld, _interval: Duration) {}
+    fn draw_hud(&self, world: &SnakeWorld, _width: usize, _height: usize) -> Vec<String> {
+        let grid = self.grid(world);
+        let (x, y) = grid.cursor();
+        vec![
+            format!("Level  : {}", self.name),
+            format!("Cursor :{:>4},{:<4} Walls :{:>6}", x, y, grid.walls()),
+            self.message.clone(),
+            String::from("Arrows - Move  /  [Space] - Wall  /  [S] - Save  /  Esc - Quit"),
+        ]
+    }
+    fn draw_title(&self, width: usize, _height: usize) -> String {
+        format!("{:^width$}", "*** SNAKE LEVEL EDITOR ***", width = width)
+    }
+    fn input(&mut self, world: &mut SnakeWorld, game_data: GameData) {
+        match game_data.action {
+            Action::Command('s') | Action::Command('S') => self.save(world),
+            action => {
+                self.message.clear();
+                if let Some(grid) = self.grid.and_then(|grid| world.object_mut(grid)) {
+                    grid.action(action);
+                }
+            }
+        }
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+/// Temporary status effects the snake gets from food.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Effect {
+    /// Moves faster.
+    SpeedUp,
+    /// Passes through its own body.
+    Invincible,
+    /// Passes through walls, but not the edges of a board that doesn't wrap.
+    Ghost,
+    /// Moves slower.
+    SlowMotion,
+    /// Pulls food within `MAGNET_RANGE` cells of its head.
+    Magnet,
+}
+
+/// Cells from the head, in both directions, that a magnet pulls food from.
+pub const MAGNET_RANGE: usize = 6;
+
+/// Time an effect lasts can't grow past this many times its duration by eating more of the
+/// same food.
+const MAX_STACK: u32 = 2;
+
+impl Effect {
+    pub fn duration(&self) -> Duration {
+        match self {
+            Effect::SpeedUp => Duration::from_secs(5),
+            Effect::Invincible => Duration::from_secs(6),
+            Effect::Ghost => Duration::from_secs(6),
+            Effect::SlowMotion => Duration::from_secs(5),
+            Effect::Magnet => Duration::from_secs(8),
+        }
+    }
+
+    /// How much the effect changes the snake's speed.
+    pub fn speed_factor(&self) -> f64 {
+        match self {
+            Effect::SpeedUp => 1.5,
+            Effect::SlowMotion => 0.5,
+            _ => 1.0,
+        }
+    }
+
+    /// Name shown in the HUD.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Effect::SpeedUp => "Fast",
+            Effect::Invincible => "Invincible",
+            Effect::Ghost => "Ghost",
+            Effect::SlowMotion => "Slow",
+            Effect::Magnet => "Magnet",
+        }
+    }
+}
+
+/// The effects active on a snake and the time each one has left.
+///
+/// Different effects stack, speed factors multiply. Getting an effect that is already
+/// active adds its duration to the time left, up to `MAX_STACK` durations.
+#[derive(Clone, Debug, Default)]
+pub struct Effects {
+    remaining: Vec<(Effect, Duration)>,
+}
+
+impl Effects {
+    pub fn add(&mut self, effect: Effect) {
+        let max = effect.duration() * MAX_STACK;
+        match self
+            .remaining
+            .iter_mut()
+            .find(|(active, _)| *active == effect)
+        {
+            Some((_, remaining)) => *remaining = (*remaining + effect.duration()).min(max),
+            None => self.remaining.push((effect, effect.duration())),
+        }
+    }
+
+    pub fn is_active(&self, effect: Effect) -> bool {
+        self.remaining.iter().any(|(active, _)| *active == effect)
+    }
+
+    /// Active effects with the time they have left, in the order they were gotten.
+    pub fn active(&self) -> &[(Effect, Duration)] {
+        &self.remaining
+    }
+
+    pub fn speed_factor(&self) -> f64 {
+        self.remaining
+            .iter()
+            .map(|(effect, _)| effect.speed_factor())
+            .product()
+    }
+
+    /// Counts `interval` off every effect and drops the ones that ran out.
+    pub fn tick(&mut self, interval: Duration) {
+        for (_, remaining) in &mut self.remaining {
+            *remaining = remaining.saturating_sub(interval);
+        }
+        self.remaining.retain(|(_, remaining)| !remaining.is_zero());
+    }
+
+    pub fn clear(&mut self) {
+        self.remaining.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effects_stack_up_to_twice_their_duration() {
+        let mut effects = Effects::default();
+        let duration = Effect::SpeedUp.duration();
+        effects.add(Effect::SpeedUp);
+        effects.tick(Duration::from_secs(1));
+        effects.add(Effect::SpeedUp);
+        assert_eq!(
+            effects.active(),
+            [(Effect::SpeedUp, duration * 2 - Duration::from_secs(1))]
+        );
+        effects.add(Effect::SpeedUp);
+        assert_eq!(effects.active(), [(Effect::SpeedUp, duration * MAX_STACK)]);
+    }
+
+    #[test]
+    fn effects_add_up_and_run_out() {
+        let mut effects = Effects::default();
+        effects.add(Effect::SlowMotion);
+        effects.add(Effect::SpeedUp);
+        effects.add(Effect::Magnet);
+        assert_eq!(effects.speed_factor(), 0.75);
+        effects.tick(Effect::SpeedUp.duration());
+        assert!(!effects.is_active(Effect::SpeedUp));
+        assert!(!effects.is_active(Effect::SlowMotion));
+        assert_eq!(effects.active(), [(Effect::Magnet, Duration::from_secs(3))]);
+        assert_eq!(effects.speed_factor(), 1.0);
+        effects.clear();
+        assert!(effects.active().is_empty());
+    }
+}
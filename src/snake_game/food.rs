@@ -0,0 +1,374 @@
+use crate::collision::Collider;
+use crate::ecs::{Entity, Glyph, System};
+use crate::game_engine::{Action, Draw, DrawLayer, Game, GameObject, GameScreen, Update};
+use crate::game_utils::Position;
+use crate::snake_game::board::Board;
+use crate::snake_game::effects::{Effect, MAGNET_RANGE};
+use crate::snake_game::snake_game_scene::{
+    SnakeGameSceneActions, SnakeWorld, BONUS_LAYER, FOOD_LAYER,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::any::Any;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Time a bonus stays on the board before it vanishes.
+pub const BONUS_LIFETIME: Duration = Duration::from_secs(8);
+
+/// A bonus blinks for this long before it vanishes.
+const BONUS_BLINK: Duration = Duration::from_secs(3);
+
+/// Milliseconds between a bonus vanishing, eaten or not, and the next one appearing.
+const BONUS_INTERVAL_MS: std::ops::RangeInclusive<u64> = 10_000..=20_000;
+
+/// Apples a bonus is worth when eaten as soon as it appears. It loses value as it ages,
+/// down to one.
+pub const BONUS_MAX_APPLES: u16 = 10;
+
+/// What the snake can eat. Every food counts as an apple and makes the snake grow, and
+/// most have an effect on top.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FoodKind {
+    Apple,
+    /// Worth five apples.
+    GoldenApple,
+    /// Takes three segments off the tail.
+    ShrinkingBerry,
+    /// Makes the snake faster for a few seconds.
+    SpeedPepper,
+    /// Gives an extra life.
+    Heart,
+    /// Lets the snake pass through its own body for a few seconds.
+    Shield,
+    /// Lets the snake pass through walls for a few seconds.
+    Ghost,
+    /// Makes the snake slower for a few seconds.
+    Snail,
+    /// Pulls food towards the snake for a few seconds.
+    Magnet,
+}
+
+impl FoodKind {
+    pub const ALL: [FoodKind; 9] = [
+        FoodKind::Apple,
+        FoodKind::GoldenApple,
+        FoodKind::ShrinkingBerry,
+        FoodKind::SpeedPepper,
+        FoodKind::Heart,
+        FoodKind::Shield,
+        FoodKind::Ghost,
+        FoodKind::Snail,
+        FoodKind::Magnet,
+    ];
+
+    /// Name of the glyph and the theme style the food is drawn with.
+    pub fn part(&self) -> &'static str {
+        match self {
+            FoodKind::Apple => "apple",
+            FoodKind::GoldenApple => "golden_apple",
+            FoodKind::ShrinkingBerry => "berry",
+            FoodKind::SpeedPepper => "pepper",
+            FoodKind::Heart => "heart",
+            FoodKind::Shield => "shield",
+            FoodKind::Ghost => "ghost",
+            FoodKind::Snail => "snail",
+            FoodKind::Magnet => "magnet",
+        }
+    }
+
+    /// How often the food is picked when a new one is placed, relative to the others.
+    pub fn spawn_weight(&self) -> u32 {
+        match self {
+            FoodKind::Apple => 60,
+            FoodKind::GoldenApple => 10,
+            FoodKind::ShrinkingBerry => 8,
+            FoodKind::SpeedPepper => 6,
+            FoodKind::Heart => 3,
+            FoodKind::Shield => 3,
+            FoodKind::Ghost => 3,
+            FoodKind::Snail => 4,
+            FoodKind::Magnet => 3,
+        }
+    }
+
+    /// What happens to the snake when it eats the food, besides counting an apple.
+    pub fn effect(&self) -> Option<SnakeGameSceneActions> {
+        match self {
+            FoodKind::Apple => None,
+            FoodKind::GoldenApple => Some(SnakeGameSceneActions::BonusApples(4)),
+            FoodKind::ShrinkingBerry => Some(SnakeGameSceneActions::Shrink(3)),
+            FoodKind::SpeedPepper => Some(SnakeGameSceneActions::PowerUp(Effect::SpeedUp)),
+            FoodKind::Heart => Some(SnakeGameSceneActions::ExtraLife),
+            FoodKind::Shield => Some(SnakeGameSceneActions::PowerUp(Effect::Invincible)),
+            FoodKind::Ghost => Some(SnakeGameSceneActions::PowerUp(Effect::Ghost)),
+            FoodKind::Snail => Some(SnakeGameSceneActions::PowerUp(Effect::SlowMotion)),
+            FoodKind::Magnet => Some(SnakeGameSceneActions::PowerUp(Effect::Magnet)),
+        }
+    }
+
+    /// Picks a kind by `spawn_weight`.
+    pub fn random(rng: &mut impl Rng) -> FoodKind {
+        let total: u32 = Self::ALL.iter().map(FoodKind::spawn_weight).sum();
+        let mut pick = rng.gen_range(0..total);
+        for kind in Self::ALL {
+            if pick < kind.spawn_weight() {
+                return kind;
+            }
+            pick -= kind.spawn_weight();
+        }
+        FoodKind::Apple
+    }
+}
+
+/// The food's component. Food is an entity with a `Position`, a `Glyph` and a `Collider`
+/// that `FoodSystem` moves to a free cell and turns into a random kind once it is eaten.
+pub struct Food {
+    board: Arc<Board>,
+    kind: FoodKind,
+    rng: StdRng,
+}
+
+impl Food {
+    /// Adds food to `world` whose every placement and kind on `board` is determined by
+    /// `seed`. The first one is always an apple.
+    pub fn spawn(world: &mut SnakeWorld, board: Arc<Board>, seed: u64) -> Entity {
+        let food = world.spawn();
+        Self::reseed(world, food, board, seed);
+        food
+    }
+
+    /// Starts `food` over as if it was just spawned with `seed`.
+    pub fn reseed(world: &mut SnakeWorld, food: Entity, board: Arc<Board>, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let position = Self::get_random_position(&board, &mut rng);
+        let kind = FoodKind::Apple;
+        world
+            .components_mut()
+            .insert(food, Food { board, kind, rng });
+        Self::place(world, food, position);
+    }
+
+    pub fn get_random_position(board: &Board, rng: &mut impl Rng) -> Position {
+        board.random_free_cell(rng)
+    }
+
+    pub fn kind(&self) -> FoodKind {
+        self.kind
+    }
+
+    /// Puts `food` on `position`, drawn and colliding as its kind.
+    fn place(world: &mut SnakeWorld, food: Entity, position: Position) {
+        let components = world.components_mut();
+        let part = match components.get::<Food>(food) {
+            Some(this) => this.kind.part(),
+            None => return,
+        };
+        components.insert(food, position);
+        components.insert(food, Glyph::new(part, DrawLayer::Items));
+        components.insert(
+            food,
+            Collider {
+                layer: FOOD_LAYER,
+                cells: vec![position.get_screen_coordinates()],
+                moving: false,
+            },
+        );
+    }
+
+    /// Moves to a free cell as a random kind.
+    fn respawn(world: &mut SnakeWorld, food: Entity) {
+        let this = match world.components_mut().get_mut::<Food>(food) {
+            Some(this) => this,
+            None => return,
+        };
+        let position = Self::get_random_position(&this.board, &mut this.rng);
+        this.kind = FoodKind::random(&mut this.rng);
+        Self::place(world, food, position);
+    }
+
+    /// Moves a cell towards `target`, along the axis it is farther on, if `target` is
+    /// within `MAGNET_RANGE` and the cell isn't a wall.
+    fn pull(world: &mut SnakeWorld, food: Entity, target: Position) {
+        let components = world.components();
+        let (this, position) = match (
+            components.get::<Food>(food),
+            components.get::<Position>(food),
+        ) {
+            (Some(this), Some(position)) => (this, position),
+            _ => return,
+        };
+        let (x, y) = position.get_screen_coordinates();
+        let (target_x, target_y) = target.get_screen_coordinates();
+        let (dx, dy) = (x.abs_diff(target_x), y.abs_diff(target_y));
+        if dx.max(dy) > MAGNET_RANGE || dx + dy <= 1 {
+            return;
+        }
+        let step = |from: usize, to: usize| if to > from { from + 1 } else { from - 1 };
+        let cell = if dx >= dy {
+            (step(x, target_x), y)
+        } else {
+            (x, step(y, target_y))
+        };
+        if !this.board.is_wall(cell) {
+            let position = Position {
+                x: cell.0 as f64,
+                y: cell.1 as f64,
+            };
+            Self::place(world, food, position);
+        }
+    }
+}
+
+/// Moves the food once it is eaten and while a magnet pulls it.
+pub struct FoodSystem;
+
+impl System<SnakeGameSceneActions> for FoodSystem {
+    fn run(&mut self, _world: &mut SnakeWorld, _interval: Duration) {}
+
+    fn subscribes(&self, event: &SnakeGameSceneActions) -> bool {
+        matches!(
+            event,
+            SnakeGameSceneActions::EatApple | SnakeGameSceneActions::Pull(_)
+        )
+    }
+
+    fn on_event(&mut self, world: &mut SnakeWorld, event: SnakeGameSceneActions) {
+        for food in world.components().entities::<Food>() {
+            match event {
+                SnakeGameSceneActions::EatApple => Food::respawn(world, food),
+                SnakeGameSceneActions::Pull(target) => Food::pull(world, food, target),
+                _ => (),
+            }
+        }
+    }
+}
+
+/// A bonus that shows up every now and then and vanishes if the snake doesn't reach it in
+/// time. The sooner it is eaten the more it is worth.
+pub struct BonusFood {
+    board: Arc<Board>,
+    rng: StdRng,
+    /// The bonus' cell and the time it has left, while it is on the board.
+    spawn: Option<(Position, Duration)>,
+    /// Time until the next bonus appears, while there is none.
+    next_spawn: Duration,
+}
+
+impl BonusFood {
+    /// Creates a bonus whose every appearance on `board` is determined by `seed`.
+    pub fn with_seed(board: Arc<Board>, seed: u64) -> BonusFood {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let next_spawn = Self::random_interval(&mut rng);
+        BonusFood {
+            board,
+            rng,
+            spawn: None,
+            next_spawn,
+        }
+    }
+
+    /// Cell of the bonus, if it is on the board.
+    pub fn position(&self) -> Option<Position> {
+        self.spawn.map(|(position, _)| position)
+    }
+
+    /// Time the bonus has left, if it is on the board.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.spawn.map(|(_, remaining)| remaining)
+    }
+
+    /// Apples the bonus is worth if it is eaten now.
+    pub fn apples(&self) -> u16 {
+        let remaining = self.remaining().unwrap_or_default();
+        let fraction = remaining.as_secs_f64() / BONUS_LIFETIME.as_secs_f64();
+        1 + ((BONUS_MAX_APPLES - 1) as f64 * fraction).round() as u16
+    }
+
+    fn random_interval(rng: &mut impl Rng) -> Duration {
+        Duration::from_millis(rng.gen_range(BONUS_INTERVAL_MS))
+    }
+
+    fn vanish(&mut self) {
+        self.spawn = None;
+        self.next_spawn = Self::random_interval(&mut self.rng);
+    }
+}
+
+impl Draw for BonusFood {
+    fn draw(&self, screen: &mut GameScreen) {
+        let (position, remaining) = match self.spawn {
+            Some(spawn) => spawn,
+            None => return,
+        };
+        // Blinks four times a second once it is about to vanish
+        if remaining < BONUS_BLINK && (remaining.as_millis() / 125) % 2 == 1 {
+            return;
+        }
+        let (x, y) = position.get_screen_coordinates();
+        let (glyph, style) = (screen.glyph("bonus"), screen.style("bonus"));
+        Game::<SnakeGameSceneActions>::draw_styled_point(screen, &glyph, style, x, y);
+    }
+    fn get_position(&self) -> Position {
+        self.position().unwrap_or(Position { x: 0.0, y: 0.0 })
+    }
+    fn layer(&self) -> DrawLayer {
+        DrawLayer::Items
+    }
+}
+
+impl Update for BonusFood {
+    fn update(&mut self, time_since_last_call: Duration) {
+        let expired = match &mut self.spawn {
+            Some((_, remaining)) => {
+                *remaining = remaining.saturating_sub(time_since_last_call);
+                remaining.is_zero()
+            }
+            None => false,
+        };
+        if expired {
+            self.vanish();
+            return;
+        }
+        if self.spawn.is_some() {
+            return;
+        }
+        self.next_spawn = self.next_spawn.saturating_sub(time_since_last_call);
+        if self.next_spawn.is_zero() {
+            let position = self.board.random_free_cell(&mut self.rng);
+            self.spawn = Some((position, BONUS_LIFETIME));
+        }
+    }
+}
+
+impl GameObject for BonusFood {
+    type Item = SnakeGameSceneActions;
+    fn action(&mut self, _action: Action) {}
+
+    fn subscribes(&self, event: &SnakeGameSceneActions) -> bool {
+        matches!(
+            event,
+            SnakeGameSceneActions::EatBonus | SnakeGameSceneActions::Restart
+        )
+    }
+
+    fn on_event(&mut self, _event: SnakeGameSceneActions) {
+        self.vanish();
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn collider(&self) -> Option<Collider> {
+        Some(Collider {
+            layer: BONUS_LAYER,
+            cells: vec![self.position()?.get_screen_coordinates()],
+            moving: false,
+        })
+    }
+}
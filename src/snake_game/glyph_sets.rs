@@ -0,0 +1,182 @@
+use crate::style::GlyphSet;
+
+/// Glyph sets by the name the settings use. `emoji` draws two columns per cell.
+pub const NAMES: [&str; 3] = ["emoji", "box", "ascii"];
+
+/// The glyph set called `name`, one of `NAMES`.
+pub fn glyph_set(name: &str) -> Option<GlyphSet> {
+    let set = match name {
+        "emoji" => GlyphSet::new(
+            name,
+            2,
+            &[
+                ("snake_head_up", "^"),
+                ("snake_head_down", "v"),
+                ("snake_head_left", "<"),
+                ("snake_head_right", ">"),
+                ("snake_body_horizontal", "━━"),
+                ("snake_body_vertical", "┃"),
+                ("snake_corner_up_left", "┛"),
+                ("snake_corner_up_right", "┗━"),
+                ("snake_corner_down_left", "┓"),
+                ("snake_corner_down_right", "┏━"),
+                ("snake_tail", "•"),
+                ("apple", "🍎"),
+                ("golden_apple", "🌟"),
+                ("berry", "🍇"),
+                ("pepper", "🔥"),
+                ("heart", "💖"),
+                ("bonus", "💎"),
+                ("shield", "💪"),
+                ("ghost", "👻"),
+                ("snail", "🐌"),
+                ("magnet", "🧲"),
+                ("ball", "🔴"),
+                ("patrol", "🚧"),
+                ("chaser", "👾"),
+                ("wall", "██"),
+                ("start", "S"),
+                ("cursor", "+"),
+                ("cursor_wall", "▓▓"),
+                ("flash", "✨"),
+                ("flash_fade", "··"),
+                ("crumble", "░░"),
+                ("dust", "··"),
+            ],
+        ),
+        "box" => GlyphSet::new(
+            name,
+            1,
+            &[
+                ("snake_head_up", "▲"),
+                ("snake_head_down", "▼"),
+                ("snake_head_left", "◀"),
+                ("snake_head_right", "▶"),
+                ("snake_body_horizontal", "━"),
+                ("snake_body_vertical", "┃"),
+                ("snake_corner_up_left", "┛"),
+                ("snake_corner_up_right", "┗"),
+                ("snake_corner_down_left", "┓"),
+                ("snake_corner_down_right", "┏"),
+                ("snake_tail", "•"),
+                ("apple", "●"),
+                ("golden_apple", "◆"),
+                ("berry", "○"),
+                ("pepper", "»"),
+                ("heart", "♥"),
+                ("bonus", "✦"),
+                ("shield", "◈"),
+                ("ghost", "◌"),
+                ("snail", "≈"),
+                ("magnet", "∩"),
+                ("ball", "◎"),
+                ("patrol", "▣"),
+                ("chaser", "☠"),
+                ("wall", "█"),
+                ("start", "S"),
+                ("cursor", "+"),
+                ("cursor_wall", "▓"),
+                ("flash", "✺"),
+                ("flash_fade", "·"),
+                ("crumble", "░"),
+                ("dust", "·"),
+            ],
+        ),
+        "ascii" => GlyphSet::new(
+            name,
+            1,
+            &[
+                ("snake_head_up", "^"),
+                ("snake_head_down", "v"),
+                ("snake_head_left", "<"),
+                ("snake_head_right", ">"),
+                ("snake_body_horizontal", "-"),
+                ("snake_body_vertical", "|"),
+                ("snake_corner_up_left", "+"),
+                ("snake_corner_up_right", "+"),
+                ("snake_corner_down_left", "+"),
+                ("snake_corner_down_right", "+"),
+                ("snake_tail", "*"),
+                ("apple", "@"),
+                ("golden_apple", "$"),
+                ("berry", "%"),
+                ("pepper", "!"),
+                ("heart", "&"),
+                ("bonus", "*"),
+                ("shield", "I"),
+                ("ghost", "G"),
+                ("snail", "Z"),
+                ("magnet", "M"),
+                ("ball", "O"),
+                ("patrol", "="),
+                ("chaser", "&"),
+                ("wall", "#"),
+                ("start", "S"),
+                ("cursor", "+"),
+                ("cursor_wall", "X"),
+                ("flash", "*"),
+                ("flash_fade", "+"),
+                ("crumble", ":"),
+                ("dust", "."),
+            ],
+        ),
+        _ => return None,
+    };
+    Some(set)
+}
+
+/// `box` if the locale's character set is UTF-8, `ascii` otherwise. Emoji are never picked
+/// on their own, since not every terminal or font draws them.
+pub fn detect() -> GlyphSet {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+        .to_lowercase();
+    if locale.contains("utf-8") || locale.contains("utf8") {
+        glyph_set("box").unwrap()
+    } else {
+        glyph_set("ascii").unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::display_width;
+
+    #[test]
+    fn glyphs_fit_their_cells() {
+        for name in NAMES {
+            let set = glyph_set(name).unwrap();
+            for (part, glyph) in set.glyphs() {
+                let width = display_width(glyph);
+                assert!(
+                    (1..=set.cell_width()).contains(&width),
+                    "{} {} is {} columns wide",
+                    name,
+                    part,
+                    width
+                );
+            }
+        }
+        assert_eq!(display_width(glyph_set("emoji").unwrap().glyph("flash")), 2);
+    }
+
+    #[test]
+    fn sets_draw_the_same_parts() {
+        let parts = |name| {
+            let mut parts: Vec<String> = glyph_set(name)
+                .unwrap()
+                .glyphs()
+                .map(|(part, _)| part.to_string())
+                .collect();
+            parts.sort();
+            parts
+        };
+        for name in NAMES {
+            assert_eq!(parts(name), parts("ascii"), "{}", name);
+        }
+    }
+}
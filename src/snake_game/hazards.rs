@@ -0,0 +1,323 @@
+use crate::collision::Collider;
+use crate::game_engine::{Action, BoxedObject, Draw, Game, GameObject, GameScreen, Update};
+use crate::game_utils::{Position, Speed};
+use crate::snake_game::board::Board;
+use crate::snake_game::level::{self, Hazard};
+use crate::snake_game::snake_game_scene::{SnakeGameSceneActions, HAZARD_LAYER};
+use std::any::Any;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Creates the object that moves `hazard` on `board`.
+pub fn create(board: Arc<Board>, hazard: &Hazard) -> BoxedObject<SnakeGameSceneActions> {
+    match hazard {
+        Hazard::Ball {
+            start,
+            direction,
+            period_ms,
+        } => Box::new(Ball::new(board, *start, *direction, *period_ms)),
+        Hazard::Patrol { path, period_ms } => Box::new(Patrol::new(path, *period_ms)),
+        Hazard::Chaser { start, period_ms } => Box::new(Chaser::new(board, *start, *period_ms)),
+    }
+}
+
+/// Counts the whole cells a hazard moves by at its speed.
+struct Pace {
+    speed: Speed,
+    /// Fraction of a cell travelled since the last move.
+    progress: f64,
+}
+
+impl Pace {
+    fn new(period_ms: u64) -> Pace {
+        Pace {
+            speed: Speed::period_in_milliseconds(u128::from(period_ms)),
+            progress: 0.0,
+        }
+    }
+
+    fn steps(&mut self, interval: Duration) -> u32 {
+        self.progress +=
+            self.speed.get_speed_steps_per_millisecond() * interval.as_secs_f64() * 1000.0;
+        let steps = self.progress.floor();
+        self.progress -= steps;
+        steps as u32
+    }
+
+    fn reset(&mut self) {
+        self.progress = 0.0;
+    }
+}
+
+fn position((x, y): (usize, usize)) -> Position {
+    Position {
+        x: x as f64,
+        y: y as f64,
+    }
+}
+
+/// Whether `event` sends hazards back to where they started: the snake lost a life
+/// against a wall or a hazard, or the game restarted.
+fn resets(event: &SnakeGameSceneActions) -> bool {
+    matches!(
+        event,
+        SnakeGameSceneActions::HitWall
+            | SnakeGameSceneActions::HitHazard
+            | SnakeGameSceneActions::Restart
+    )
+}
+
+/// A hazard runs into the snake as much as the snake runs into it.
+fn hazard_collider(position: Position) -> Collider {
+    Collider {
+        layer: HAZARD_LAYER,
+        cells: vec![position.get_screen_coordinates()],
+        moving: true,
+    }
+}
+
+fn draw(screen: &mut GameScreen, part: &str, (x, y): (usize, usize)) {
+    let (glyph, style) = (screen.glyph(part), screen.style(part));
+    Game::<SnakeGameSceneActions>::draw_styled_point(screen, &glyph, style, x, y);
+}
+
+/// Whether a hazard can't move to `(x, y)`: it is off the board or a wall.
+fn blocked(board: &Board, (x, y): (i64, i64)) -> bool {
+    x < 1 || y < 1 || {
+        let cell = (x as usize, y as usize);
+        !board.contains(cell) || board.is_wall(cell)
+    }
+}
+
+/// Moves in a straight or diagonal line and bounces off walls and the edges of the board.
+pub struct Ball {
+    board: Arc<Board>,
+    start: (usize, usize),
+    start_direction: (i8, i8),
+    cell: (usize, usize),
+    direction: (i8, i8),
+    pace: Pace,
+}
+
+impl Ball {
+    pub fn new(
+        board: Arc<Board>,
+        start: (usize, usize),
+        direction: (i8, i8),
+        period_ms: u64,
+    ) -> Ball {
+        Ball {
+            board,
+            start,
+            start_direction: direction,
+            cell: start,
+            direction,
+            pace: Pace::new(period_ms),
+        }
+    }
+
+    /// Moves a cell, turning back on the axis that is blocked, or both if need be.
+    fn step(&mut self) {
+        let (x, y) = (self.cell.0 as i64, self.cell.1 as i64);
+        let (dx, dy) = self.direction;
+        for (dx, dy) in [(dx, dy), (-dx, dy), (dx, -dy), (-dx, -dy)] {
+            let next = (x + i64::from(dx), y + i64::from(dy));
+            if !blocked(&self.board, next) {
+                self.direction = (dx, dy);
+                self.cell = (next.0 as usize, next.1 as usize);
+                return;
+            }
+        }
+    }
+}
+
+/// Goes round a fixed path of straight lines.
+pub struct Patrol {
+    /// Every cell along the path, in order. The last one is next to the first.
+    route: Vec<(usize, usize)>,
+    index: usize,
+    pace: Pace,
+}
+
+impl Patrol {
+    pub fn new(path: &[(usize, usize)], period_ms: u64) -> Patrol {
+        Patrol {
+            route: level::patrol_route(path),
+            index: 0,
+            pace: Pace::new(period_ms),
+        }
+    }
+}
+
+/// Heads for the snake's head, going around walls where it can.
+pub struct Chaser {
+    board: Arc<Board>,
+    start: (usize, usize),
+    cell: (usize, usize),
+    target: Option<(usize, usize)>,
+    pace: Pace,
+}
+
+impl Chaser {
+    pub fn new(board: Arc<Board>, start: (usize, usize), period_ms: u64) -> Chaser {
+        Chaser {
+            board,
+            start,
+            cell: start,
+            target: None,
+            pace: Pace::new(period_ms),
+        }
+    }
+
+    /// Moves a cell along the axis the target is farther on, or along the other one if
+    /// that is blocked.
+    fn step(&mut self) {
+        let (target_x, target_y) = match self.target {
+            Some(target) if target != self.cell => target,
+            _ => return,
+        };
+        let (x, y) = (self.cell.0 as i64, self.cell.1 as i64);
+        let (dx, dy) = (
+            (target_x as i64 - x).signum(),
+            (target_y as i64 - y).signum(),
+        );
+        let horizontal = (x + dx, y);
+        let vertical = (x, y + dy);
+        let moves = if (target_x as i64 - x).abs() >= (target_y as i64 - y).abs() {
+            [horizontal, vertical]
+        } else {
+            [vertical, horizontal]
+        };
+        if let Some(next) = moves
+            .into_iter()
+            .find(|next| *next != (x, y) && !blocked(&self.board, *next))
+        {
+            self.cell = (next.0 as usize, next.1 as usize);
+        }
+    }
+}
+
+impl Draw for Ball {
+    fn draw(&self, screen: &mut GameScreen) {
+        draw(screen, "ball", self.cell);
+    }
+    fn get_position(&self) -> Position {
+        position(self.cell)
+    }
+}
+
+impl Draw for Patrol {
+    fn draw(&self, screen: &mut GameScreen) {
+        draw(screen, "patrol", self.route[self.index]);
+    }
+    fn get_position(&self) -> Position {
+        position(self.route[self.index])
+    }
+}
+
+impl Draw for Chaser {
+    fn draw(&self, screen: &mut GameScreen) {
+        draw(screen, "chaser", self.cell);
+    }
+    fn get_position(&self) -> Position {
+        position(self.cell)
+    }
+}
+
+impl Update for Ball {
+    fn update(&mut self, time_since_last_call: Duration) {
+        for _ in 0..self.pace.steps(time_since_last_call) {
+            self.step();
+        }
+    }
+}
+
+impl Update for Patrol {
+    fn update(&mut self, time_since_last_call: Duration) {
+        let steps = self.pace.steps(time_since_last_call) as usize;
+        self.index = (self.index + steps) % self.route.len();
+    }
+}
+
+impl Update for Chaser {
+    fn update(&mut self, time_since_last_call: Duration) {
+        for _ in 0..self.pace.steps(time_since_last_call) {
+            self.step();
+        }
+    }
+}
+
+impl GameObject for Ball {
+    type Item = SnakeGameSceneActions;
+
+    fn action(&mut self, _action: Action) {}
+    fn subscribes(&self, event: &SnakeGameSceneActions) -> bool {
+        resets(event)
+    }
+    fn on_event(&mut self, _event: SnakeGameSceneActions) {
+        self.cell = self.start;
+        self.direction = self.start_direction;
+        self.pace.reset();
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn collider(&self) -> Option<Collider> {
+        Some(hazard_collider(self.get_position()))
+    }
+}
+
+impl GameObject for Patrol {
+    type Item = SnakeGameSceneActions;
+
+    fn action(&mut self, _action: Action) {}
+    fn subscribes(&self, event: &SnakeGameSceneActions) -> bool {
+        resets(event)
+    }
+    fn on_event(&mut self, _event: SnakeGameSceneActions) {
+        self.index = 0;
+        self.pace.reset();
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn collider(&self) -> Option<Collider> {
+        Some(hazard_collider(self.get_position()))
+    }
+}
+
+impl GameObject for Chaser {
+    type Item = SnakeGameSceneActions;
+
+    fn action(&mut self, _action: Action) {}
+    fn subscribes(&self, event: &SnakeGameSceneActions) -> bool {
+        matches!(event, SnakeGameSceneActions::Chase(_)) || resets(event)
+    }
+    fn on_event(&mut self, event: SnakeGameSceneActions) {
+        match event {
+            SnakeGameSceneActions::Chase(head) => {
+                self.target = Some(head.get_screen_coordinates());
+            }
+            _ => {
+                self.cell = self.start;
+                self.target = None;
+                self.pace.reset();
+            }
+        }
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn collider(&self) -> Option<Collider> {
+        Some(hazard_collider(self.get_position()))
+    }
+}
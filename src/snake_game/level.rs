@@ -0,0 +1,469 @@
+/// Walls to play in, read from a level file.
+///
+/// A level file has directives followed by a `map` line and the rows of the board, top
+/// first. In the map `#` is a wall and `.` or a space is free. Lines starting with `//`
+/// are comments. Besides `name`, the directives place moving hazards, see `Hazard`.
+///
+/// ```text
+/// // A box with a gap on each side
+/// name Box
+/// ball 3,3 1,1 150
+/// map
+/// ####### #######
+/// #             #
+///
+/// #             #
+/// ####### #######
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Level {
+    pub name: String,
+    /// Wall cells, numbered from 1 like board cells.
+    pub walls: Vec<(usize, usize)>,
+    pub hazards: Vec<Hazard>,
+}
+
+/// A moving obstacle, as placed by a level directive. Cells are numbered like walls and
+/// every hazard moves a cell each `period_ms` milliseconds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Hazard {
+    /// `ball X,Y DX,DY MS`: moves by `DX,DY`, each -1, 0 or 1, bouncing off walls and edges.
+    Ball {
+        start: (usize, usize),
+        direction: (i8, i8),
+        period_ms: u64,
+    },
+    /// `patrol MS X,Y X,Y ...`: goes along the straight lines between the points, and from
+    /// the last one back to the first.
+    Patrol {
+        path: Vec<(usize, usize)>,
+        period_ms: u64,
+    },
+    /// `chaser X,Y MS`: heads for the snake's head.
+    Chaser {
+        start: (usize, usize),
+        period_ms: u64,
+    },
+}
+
+impl Hazard {
+    /// Cells the hazard starts on or goes through on the way.
+    fn cells(&self) -> Vec<(usize, usize)> {
+        match self {
+            Hazard::Ball { start, .. } | Hazard::Chaser { start, .. } => vec![*start],
+            Hazard::Patrol { path, .. } => patrol_route(path),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Hazard::Ball {
+                direction: (0, 0), ..
+            } => Err(String::from("a ball needs a direction")),
+            Hazard::Ball { period_ms: 0, .. }
+            | Hazard::Patrol { period_ms: 0, .. }
+            | Hazard::Chaser { period_ms: 0, .. } => Err(String::from("a hazard needs a period")),
+            Hazard::Patrol { path, .. } => {
+                let mut turns = path.iter().zip(path.iter().cycle().skip(1));
+                match turns.find(|((x, y), (next_x, next_y))| x != next_x && y != next_y) {
+                    Some(((x, y), (next_x, next_y))) => Err(format!(
+                        "patrol from {},{} to {},{} is not a straight line",
+                        x, y, next_x, next_y
+                    )),
+                    None => Ok(()),
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Every cell a patrol along `path` goes through, in order, a step at a time from each
+/// point to the next and from the last one back to the first.
+pub fn patrol_route(path: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let step = |from: usize, to: usize| match to.cmp(&from) {
+        std::cmp::Ordering::Greater => from + 1,
+        std::cmp::Ordering::Less => from - 1,
+        std::cmp::Ordering::Equal => from,
+    };
+    let mut route = Vec::new();
+    for (from, to) in path.iter().zip(path.iter().cycle().skip(1)) {
+        let (mut x, mut y) = *from;
+        while (x, y) != *to {
+            route.push((x, y));
+            (x, y) = (step(x, to.0), step(y, to.1));
+        }
+    }
+    if route.is_empty() {
+        route.push(path[0]);
+    }
+    route
+}
+
+impl std::str::FromStr for Hazard {
+    type Err = String;
+
+    /// Reads a hazard directive, such as `ball 3,3 1,1 150`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split_whitespace();
+        let directive = fields.next().unwrap_or_default();
+        let fields: Vec<&str> = fields.collect();
+        let cell = |field: &str| {
+            field
+                .split_once(',')
+                .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+                .ok_or_else(|| format!("expected a cell as `x,y`, found `{}`", field))
+        };
+        let period = |field: &str| {
+            field
+                .parse()
+                .map_err(|_| format!("expected a period in milliseconds, found `{}`", field))
+        };
+        let hazard = match (directive, fields.as_slice()) {
+            ("ball", [start, direction, period_ms]) => {
+                let direction = direction
+                    .split_once(',')
+                    .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+                    .filter(|(x, y): &(i8, i8)| x.abs() <= 1 && y.abs() <= 1)
+                    .ok_or_else(|| {
+                        format!("expected a direction as `dx,dy`, found `{}`", direction)
+                    })?;
+                Hazard::Ball {
+                    start: cell(start)?,
+                    direction,
+                    period_ms: period(period_ms)?,
+                }
+            }
+            ("ball", _) => return Err(String::from("expected `ball X,Y DX,DY MS`")),
+            ("patrol", [period_ms, path @ ..]) if path.len() >= 2 => Hazard::Patrol {
+                path: path
+                    .iter()
+                    .map(|field| cell(field))
+                    .collect::<Result<_, _>>()?,
+                period_ms: period(period_ms)?,
+            },
+            ("patrol", _) => return Err(String::from("expected `patrol MS X,Y X,Y ...`")),
+            ("chaser", [start, period_ms]) => Hazard::Chaser {
+                start: cell(start)?,
+                period_ms: period(period_ms)?,
+            },
+            ("chaser", _) => return Err(String::from("expected `chaser X,Y MS`")),
+            (other, _) => return Err(format!("unknown hazard `{}`", other)),
+        };
+        hazard.validate()?;
+        Ok(hazard)
+    }
+}
+
+impl std::fmt::Display for Hazard {
+    /// Writes the directive `from_str` reads back.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hazard::Ball {
+                start: (x, y),
+                direction: (dx, dy),
+                period_ms,
+            } => write!(f, "ball {},{} {},{} {}", x, y, dx, dy, period_ms),
+            Hazard::Patrol { path, period_ms } => {
+                write!(f, "patrol {}", period_ms)?;
+                for (x, y) in path {
+                    write!(f, " {},{}", x, y)?;
+                }
+                Ok(())
+            }
+            Hazard::Chaser {
+                start: (x, y),
+                period_ms,
+            } => write!(f, "chaser {},{} {}", x, y, period_ms),
+        }
+    }
+}
+
+impl Level {
+    pub fn load(path: &str) -> Result<Level, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn parse(text: &str) -> Result<Level, String> {
+        let mut level = Level::default();
+        let mut lines = text.lines().enumerate();
+        for (i, line) in lines.by_ref() {
+            let error = |message: &str| format!("line {}: {}", i + 1, message);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let (directive, value) = line.split_once(' ').unwrap_or((line, ""));
+            match directive {
+                "name" => level.name = value.trim().to_string(),
+                "ball" | "patrol" | "chaser" => level
+                    .hazards
+                    .push(line.parse().map_err(|e: String| error(&e))?),
+                "map" => break,
+                other => return Err(error(&format!("unknown directive `{}`", other))),
+            }
+        }
+        let mut y = 0;
+        for (i, line) in lines {
+            if line.starts_with("//") {
+                continue;
+            }
+            y += 1;
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '#' => level.walls.push((x + 1, y)),
+                    ' ' | '.' => (),
+                    c => {
+                        return Err(format!(
+                            "line {}: unknown cell `{}`, expected `#`, `.` or a space",
+                            i + 1,
+                            c
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(level)
+    }
+
+    /// Checks that the level fits a `width` x `height` board and leaves the start free.
+    pub fn validate(
+        &self,
+        width: usize,
+        height: usize,
+        start: (usize, usize),
+    ) -> Result<(), String> {
+        let off_board = |x: usize, y: usize| {
+            format!(
+                "column {}, row {} is off the board, columns and rows start at 1",
+                x, y
+            )
+        };
+        if let Some((x, y)) = self.walls.iter().find(|(x, y)| *x == 0 || *y == 0) {
+            return Err(format!("wall at {}", off_board(*x, *y)));
+        }
+        if let Some((x, y)) = self.walls.iter().find(|(x, y)| *x > width || *y > height) {
+            return Err(format!(
+                "wall at column {}, row {} is outside the {}x{} board",
+                x, y, width, height
+            ));
+        }
+        if self.walls.contains(&start) {
+            return Err(format!(
+                "wall at column {}, row {} is on the snake's starting cell",
+                start.0, start.1
+            ));
+        }
+        for hazard in &self.hazards {
+            for (x, y) in hazard.cells() {
+                if x == 0 || y == 0 {
+                    return Err(format!("`{}`: {}", hazard, off_board(x, y)));
+                }
+                if !(1..=width).contains(&x) || !(1..=height).contains(&y) {
+                    return Err(format!(
+                        "`{}`: column {}, row {} is outside the {}x{} board",
+                        hazard, x, y, width, height
+                    ));
+                }
+                if self.walls.contains(&(x, y)) {
+                    return Err(format!("`{}`: column {}, row {} is a wall", hazard, x, y));
+                }
+                if (x, y) == start {
+                    return Err(format!(
+                        "`{}`: column {}, row {} is the snake's starting cell",
+                        hazard, x, y
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The level in the format `parse` reads, with a `width` x `height` map.
+    pub fn to_text(&self, width: usize, height: usize) -> String {
+        let mut rows = vec![vec!['.'; width]; height];
+        for (x, y) in &self.walls {
+            if let Some(cell) = rows.get_mut(y - 1).and_then(|row| row.get_mut(x - 1)) {
+                *cell = '#';
+            }
+        }
+        let mut text = String::new();
+        if !self.name.is_empty() {
+            text.push_str(&format!("name {}\n", self.name));
+        }
+        for hazard in &self.hazards {
+            text.push_str(&format!("{}\n", hazard));
+        }
+        text.push_str("map\n");
+        for row in rows {
+            text.extend(row);
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn save(&self, path: &str, width: usize, height: usize) -> Result<(), String> {
+        std::fs::write(path, self.to_text(width, height)).map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOX: &str = "// A box with a gap
+name Box
+ball 3,3 1,-1 150
+patrol 200 2,5 6,5
+map
+####.
+#...#
+// the middle row
+#   #
+";
+
+    #[test]
+    fn levels_are_read_back_as_written() {
+        let level = Level::parse(BOX).unwrap();
+        assert_eq!(level.name, "Box");
+        assert_eq!(
+            level.walls,
+            [
+                (1, 1),
+                (2, 1),
+                (3, 1),
+                (4, 1),
+                (1, 2),
+                (5, 2),
+                (1, 3),
+                (5, 3)
+            ]
+        );
+        assert_eq!(
+            level.hazards,
+            [
+                Hazard::Ball {
+                    start: (3, 3),
+                    direction: (1, -1),
+                    period_ms: 150
+                },
+                Hazard::Patrol {
+                    path: vec![(2, 5), (6, 5)],
+                    period_ms: 200
+                },
+            ]
+        );
+        let text = level.to_text(6, 6);
+        assert!(text.starts_with("name Box\nball 3,3 1,-1 150\npatrol 200 2,5 6,5\nmap\n"));
+        let read = Level::parse(&text).unwrap();
+        assert_eq!((read.walls, read.hazards), (level.walls, level.hazards));
+    }
+
+    #[test]
+    fn bad_lines_are_reported_with_their_number() {
+        let error = |text: &str| Level::parse(text).unwrap_err();
+        assert_eq!(
+            error("name A\nwall 3\nmap"),
+            "line 2: unknown directive `wall`"
+        );
+        assert_eq!(
+            error("map\n#.#\n#x#"),
+            "line 3: unknown cell `x`, expected `#`, `.` or a space"
+        );
+        assert_eq!(
+            error("ball 3,3 1,1"),
+            "line 1: expected `ball X,Y DX,DY MS`"
+        );
+        assert_eq!(
+            error("ball 3,3 0,0 100"),
+            "line 1: a ball needs a direction"
+        );
+        assert_eq!(
+            error("chaser 3;3 100"),
+            "line 1: expected a cell as `x,y`, found `3;3`"
+        );
+        assert_eq!(
+            error("chaser 3,3 fast"),
+            "line 1: expected a period in milliseconds, found `fast`"
+        );
+        assert_eq!(error("chaser 3,3 0"), "line 1: a hazard needs a period");
+        assert_eq!(
+            error("patrol 100 2,2 4,4"),
+            "line 1: patrol from 2,2 to 4,4 is not a straight line"
+        );
+    }
+
+    #[test]
+    fn levels_have_to_fit_the_board() {
+        let level = Level::parse(BOX).unwrap();
+        assert_eq!(level.validate(6, 6, (3, 2)), Ok(()));
+        assert_eq!(
+            level.validate(4, 6, (3, 2)),
+            Err(String::from(
+                "wall at column 5, row 2 is outside the 4x6 board"
+            ))
+        );
+        assert_eq!(
+            level.validate(6, 6, (1, 2)),
+            Err(String::from(
+                "wall at column 1, row 2 is on the snake's starting cell"
+            ))
+        );
+        assert_eq!(
+            level.validate(6, 4, (3, 2)),
+            Err(String::from(
+                "`patrol 200 2,5 6,5`: column 2, row 5 is outside the 6x4 board"
+            ))
+        );
+        assert_eq!(
+            level.validate(6, 6, (3, 3)),
+            Err(String::from(
+                "`ball 3,3 1,-1 150`: column 3, row 3 is the snake's starting cell"
+            ))
+        );
+    }
+
+    #[test]
+    fn patrols_are_checked_along_their_whole_route() {
+        assert_eq!(
+            patrol_route(&[(2, 2), (4, 2), (4, 3), (2, 3)]),
+            [(2, 2), (3, 2), (4, 2), (4, 3), (3, 3), (2, 3)]
+        );
+        let across_wall = Level::parse("patrol 100 1,4 5,4\nmap\n\n\n\n  #\n").unwrap();
+        assert_eq!(
+            across_wall.validate(6, 6, (3, 2)),
+            Err(String::from(
+                "`patrol 100 1,4 5,4`: column 3, row 4 is a wall"
+            ))
+        );
+        let across_start = Level::parse("patrol 100 2,1 2,5\nmap\n").unwrap();
+        assert_eq!(
+            across_start.validate(6, 6, (2, 3)),
+            Err(String::from(
+                "`patrol 100 2,1 2,5`: column 2, row 3 is the snake's starting cell"
+            ))
+        );
+    }
+
+    #[test]
+    fn columns_and_rows_start_at_1() {
+        let wall = Level {
+            walls: vec![(3, 0)],
+            ..Level::default()
+        };
+        assert_eq!(
+            wall.validate(6, 6, (2, 2)),
+            Err(String::from(
+                "wall at column 3, row 0 is off the board, columns and rows start at 1"
+            ))
+        );
+        let chaser = Level::parse("chaser 0,4 100\nmap\n").unwrap();
+        assert_eq!(
+            chaser.validate(6, 6, (2, 2)),
+            Err(String::from(
+                "`chaser 0,4 100`: column 0, row 4 is off the board, columns and rows start at 1"
+            ))
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! The snake game, built on `game_engine`.
+//!
+//! `SnakeGameScene` holds the rules. `Snake` and `Food` are entities run by their systems,
+//! `Walls` and the moving `hazards` are its objects, all sharing one `Board`, and `scoring`
+//! keeps the score. The other modules run the same scene in other ways: recorded and
+//! replayed, as a training environment, or played by bots in batch. `editor` draws the
+//! level files that `level` reads. `themes` and `glyph_sets` are the built-in looks.
+
+pub mod animations;
+pub mod board;
+pub mod bots;
+pub mod editor;
+pub mod effects;
+pub mod food;
+pub mod glyph_sets;
+pub mod hazards;
+pub mod level;
+pub mod replay;
+pub mod scores;
+pub mod scoring;
+pub mod simulator;
+pub mod snake;
+pub mod snake_env;
+pub mod snake_game_scene;
+pub mod snake_parts;
+pub mod themes;
+pub mod walls;
+
+pub use snake_game_scene::{Difficulty, SnakeGameConfig, SnakeGameScene, SnakeGameSceneActions};
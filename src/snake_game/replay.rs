@@ -0,0 +1,638 @@
+use crate::game_engine::{Action, GameData, GameScene, InputRecorder};
+use crate::snake_game::level::Level;
+use crate::snake_game::snake_game_scene::{
+    Respawn, SnakeGameConfig, SnakeGameScene, SnakeGameSceneActions, SnakeWorld,
+};
+use std::any::Any;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::time::Duration;
+
+const REPLAY_HEADER: &str = "snake-replay 1";
+const SEEK_TICKS: u64 = 250;
+const MAX_SPEED: u32 = 8;
+
+#[derive(Copy, Clone, Debug)]
+pub struct ReplayInput {
+    /// Number of ticks the game had run when the input arrived.
+    pub tick: u64,
+    pub game_data: GameData,
+}
+
+/// A recorded game: everything needed to rebuild it tick by tick.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    /// The game as it was configured, without a high-score table.
+    pub config: SnakeGameConfig,
+    pub inputs: Vec<ReplayInput>,
+    /// Events the game logged and the tick they were dispatched at. Playback doesn't need
+    /// them, they're there to read.
+    pub events: Vec<(u64, String)>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Replay, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    pub fn parse(text: &str) -> Result<Replay, String> {
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, REPLAY_HEADER)) => (),
+            _ => return Err(format!("not a replay file, expected `{}`", REPLAY_HEADER)),
+        }
+        let mut replay = Replay {
+            config: SnakeGameConfig::default(),
+            inputs: Vec::new(),
+            events: Vec::new(),
+        };
+        for (i, line) in lines {
+            let error = |message: &str| format!("line {}: {}", i + 1, message);
+            let number = |value: Option<&str>| {
+                value
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .ok_or_else(|| error("expected a number"))
+            };
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("seed") => replay.config.seed = number(fields.next())?,
+                Some("board") => {
+                    replay.config.width = number(fields.next())? as usize;
+                    replay.config.height = number(fields.next())? as usize;
+                }
+                Some("difficulty") => {
+                    replay.config.difficulty = fields
+                        .next()
+                        .unwrap_or_default()
+                        .parse()
+                        .map_err(|e: String| error(&e))?
+                }
+                Some("wrap") => replay.config.wrap = number(fields.next())? != 0,
+                Some("respawn") => {
+                    replay.config.respawn = Some(Respawn {
+                        countdown: Duration::from_millis(number(fields.next())?),
+                        invulnerable: Duration::from_millis(number(fields.next())?),
+                    })
+                }
+                Some("level") => {
+                    let name = line.trim_start()["level".len()..].trim();
+                    replay.config.level.get_or_insert_with(Level::default).name = name.to_string();
+                }
+                Some("walls") => {
+                    let level = replay.config.level.get_or_insert_with(Level::default);
+                    for wall in fields {
+                        let (x, y) = wall
+                            .split_once(',')
+                            .and_then(|(x, y)| Some((x.parse().ok()?, y.parse().ok()?)))
+                            .ok_or_else(|| error("expected walls as `x,y`"))?;
+                        level.walls.push((x, y));
+                    }
+                }
+                Some("hazard") => {
+                    let hazard = line.trim_start()["hazard".len()..]
+                        .parse()
+                        .map_err(|e: String| error(&e))?;
+                    let level = replay.config.level.get_or_insert_with(Level::default);
+                    level.hazards.push(hazard);
+                }
+                Some("tick_rate") => replay.config.tick_rate = number(fields.next())? as usize,
+                Some("input") => {
+                    let tick = number(fields.next())?;
+                    let action = fields
+                        .next()
+                        .ok_or_else(|| error("expected an action"))?
+                        .parse::<Action>()
+                        .map_err(|e| error(&e))?;
+                    replay.inputs.push(ReplayInput {
+                        tick,
+                        game_data: GameData { action },
+                    });
+                }
+                Some("event") => {
+                    let tick = number(fields.next())?;
+                    replay
+                        .events
+                        .push((tick, fields.collect::<Vec<_>>().join(" ")));
+                }
+                Some(other) => return Err(error(&format!("unknown entry `{}`", other))),
+                None => (),
+            }
+        }
+        replay.config.validate()?;
+        Ok(replay)
+    }
+
+    pub fn last_tick(&self) -> u64 {
+        self.inputs.last().map_or(0, |input| input.tick)
+    }
+}
+
+/// Writes a replay file as the game runs.
+///
+/// Recording stops at the first error writing the file, which `finish` reports.
+pub struct ReplayWriter {
+    path: String,
+    file: BufWriter<File>,
+    error: Option<std::io::Error>,
+}
+
+impl ReplayWriter {
+    pub fn create(path: &str, config: &SnakeGameConfig) -> std::io::Result<ReplayWriter> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{}", REPLAY_HEADER)?;
+        writeln!(file, "seed {}", config.seed)?;
+        writeln!(file, "board {} {}", config.width, config.height)?;
+        writeln!(file, "difficulty {}", config.difficulty)?;
+        writeln!(file, "wrap {}", u8::from(config.wrap))?;
+        if let Some(respawn) = config.respawn {
+            writeln!(
+                file,
+                "respawn {} {}",
+                respawn.countdown.as_millis(),
+                respawn.invulnerable.as_millis()
+            )?;
+        }
+        if let Some(level) = &config.level {
+            writeln!(file, "level {}", level.name)?;
+            let walls: Vec<String> = level
+                .walls
+                .iter()
+                .map(|(x, y)| format!("{},{}", x, y))
+                .collect();
+            writeln!(file, "walls {}", walls.join(" "))?;
+            for hazard in &level.hazards {
+                writeln!(file, "hazard {}", hazard)?;
+            }
+        }
+        writeln!(file, "tick_rate {}", config.tick_rate)?;
+        Ok(ReplayWriter {
+            path: path.to_string(),
+            file,
+            error: None,
+        })
+    }
+
+    fn write_line(&mut self, line: std::fmt::Arguments) {
+        if self.error.is_none() {
+            self.error = writeln!(self.file, "{}", line).err();
+        }
+    }
+}
+
+impl InputRecorder for ReplayWriter {
+    fn record(&mut self, tick: u64, game_data: GameData) {
+        self.write_line(format_args!("input {} {}", tick, game_data.action));
+    }
+    fn record_event(&mut self, tick: u64, event: &str) {
+        self.write_line(format_args!("event {} {}", tick, event));
+    }
+    fn finish(&mut self) -> Result<(), String> {
+        if self.error.is_none() {
+            self.error = self.file.flush().err();
+        }
+        match self.error.take() {
+            Some(error) => Err(format!("{}: {}", self.path, error)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Plays a `Replay` back through a `SnakeGameScene`.
+///
+/// Recorded inputs are fed to the scene at the tick they were received, so the game unfolds
+/// exactly as it was played. Live keys only control the playback.
+pub struct ReplayScene {
+    scene: SnakeGameScene,
+    replay: Replay,
+    tick: u64,
+    next_input: usize,
+    paused: bool,
+    speed: u32,
+}
+
+impl ReplayScene {
+    pub fn new(replay: Replay) -> ReplayScene {
+        ReplayScene {
+            scene: SnakeGameScene::with_config(replay.config.clone()),
+            replay,
+            tick: 0,
+            next_input: 0,
+            paused: false,
+            speed: 1,
+        }
+    }
+
+    pub fn scene(&self) -> &SnakeGameScene {
+        &self.scene
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Whether playback reached the last recorded input.
+    pub fn ended(&self) -> bool {
+        self.tick >= self.replay.last_tick()
+    }
+
+    /// Sends the scene every recorded input that arrived before the current tick's update,
+    /// dispatching the events of each before the next as the engine did.
+    fn apply_inputs(&mut self, world: &mut SnakeWorld) {
+        while let Some(input) = self.replay.inputs.get(self.next_input) {
+            if input.tick > self.tick {
+                break;
+            }
+            if input.game_data.action != Action::Quit {
+                self.scene.input(world, input.game_data);
+            }
+            world.dispatch_events(&mut self.scene);
+            self.next_input += 1;
+        }
+    }
+
+    fn advance(&mut self, world: &mut SnakeWorld) {
+        self.tick += 1;
+        self.apply_inputs(world);
+    }
+
+    /// Runs a tick of the game between two of the engine's, for stepping and seeking.
+    fn step(&mut self, world: &mut SnakeWorld) {
+        if self.ended() {
+            return;
+        }
+        world.step(&mut self.scene, world.config().tick_interval());
+        self.advance(world);
+    }
+
+    /// Plays on to `tick`, or back from the start of the game for an earlier one, with a
+    /// world and scene built again so nothing of the later ticks is left.
+    fn seek(&mut self, world: &mut SnakeWorld, tick: u64) {
+        if tick < self.tick {
+            world.clear();
+            self.scene = SnakeGameScene::with_config(self.replay.config.clone());
+            self.tick = 0;
+            self.next_input = 0;
+            self.load(world);
+        }
+        while self.tick < tick && !self.ended() {
+            self.step(world);
+        }
+    }
+
+    fn controls(&mut self, world: &mut SnakeWorld, c: char) {
+        match c {
+            ' ' => self.paused = !self.paused,
+            '.' if self.paused => self.step(world),
+            'f' | 'F' => {
+                self.speed = if self.speed >= MAX_SPEED {
+                    1
+                } else {
+                    self.speed * 2
+                };
+            }
+            _ => (),
+        }
+    }
+}
+
+impl GameScene<SnakeGameSceneActions> for ReplayScene {
+    fn load(&mut self, world: &mut SnakeWorld) {
+        self.scene.load(world);
+        self.apply_inputs(world);
+    }
+    fn update(&mut self, world: &mut SnakeWorld, interval: Duration) {
+        self.scene.update(world, interval);
+    }
+    fn speed(&self) -> u32 {
+        self.speed
+    }
+    /// Recorded inputs arrive once a tick is over, as they did while the game was played.
+    fn after_tick(&mut self, world: &mut SnakeWorld) {
+        if !self.paused() {
+            self.advance(world);
+        }
+    }
+    fn draw_hud(&self, world: &SnakeWorld, width: usize, height: usize) -> Vec<String> {
+        let hud = self.scene.draw_hud(world, width, height);
+        let state = if self.ended() {
+            "end"
+        } else if self.paused {
+            "paused"
+        } else {
+            "playing"
+        };
+        let status = format!(
+            "Replay :{:>6}/{} ticks  x{}  {}",
+            self.tick,
+            self.replay.last_tick(),
+            self.speed,
+            state
+        );
+        let controls = String::from("[Space] Pause  [.] Step  [F] Speed  Left/Right - Seek");
+        vec![hud[1].clone(), hud[2].clone(), status, controls]
+    }
+    fn draw_title(&self, width: usize, _height: usize) -> String {
+        let title = "*** SNAKE REPLAY ***";
+        format!("{:^width$}", title, width = width)
+    }
+    fn input(&mut self, world: &mut SnakeWorld, game_data: GameData) {
+        match game_data.action {
+            Action::Command(c) => self.controls(world, c),
+            Action::MoveLeft => self.seek(world, self.tick.saturating_sub(SEEK_TICKS)),
+            Action::MoveRight => self.seek(world, self.tick + SEEK_TICKS),
+            _ => (),
+        }
+    }
+    fn subscribes(&self, event: &SnakeGameSceneActions) -> bool {
+        self.scene.subscribes(event)
+    }
+    fn on_event(&mut self, world: &mut SnakeWorld, event: SnakeGameSceneActions) {
+        self.scene.on_event(world, event);
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn paused(&self) -> bool {
+        self.paused || self.ended()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_engine::{Game, Simulation};
+    use crate::snake_game::Difficulty;
+
+    const TICKS: u64 = 3000;
+
+    fn config() -> SnakeGameConfig {
+        SnakeGameConfig {
+            width: 24,
+            height: 12,
+            seed: 5,
+            respawn: Some(Difficulty::Normal.respawn()),
+            ..SnakeGameConfig::default()
+        }
+    }
+
+    /// Turns every so often, so the snake eats, crashes and comes back, and restarts once.
+    fn inputs() -> Vec<ReplayInput> {
+        let turns = [
+            Action::MoveUp,
+            Action::MoveRight,
+            Action::MoveDown,
+            Action::MoveRight,
+        ];
+        let mut inputs: Vec<ReplayInput> = (1..TICKS / 37)
+            .map(|i| ReplayInput {
+                tick: i * 37,
+                game_data: GameData {
+                    action: turns[i as usize % turns.len()],
+                },
+            })
+            .collect();
+        inputs.insert(
+            30,
+            ReplayInput {
+                tick: inputs[30].tick,
+                game_data: GameData {
+                    action: Action::Restart,
+                },
+            },
+        );
+        inputs.push(ReplayInput {
+            tick: TICKS,
+            game_data: GameData {
+                action: Action::Quit,
+            },
+        });
+        inputs
+    }
+
+    fn simulation<T: GameScene<SnakeGameSceneActions> + Send + 'static>(
+        scene: T,
+    ) -> Simulation<SnakeGameSceneActions> {
+        let mut game = Game::new(Box::new(scene));
+        game.set_config(config().engine_config());
+        let mut simulation = game.simulation();
+        simulation.load();
+        simulation
+    }
+
+    /// Score, apples, lives lost, length and head of the snake in the game being played.
+    type Outcome = (u32, u16, u32, usize, (usize, usize));
+
+    fn outcome(scene: &SnakeGameScene, world: &SnakeWorld) -> Outcome {
+        let snake = scene.snake(world);
+        let head = snake.head(world).get_screen_coordinates();
+        let (score, apples) = (scene.score().total(), snake.apples());
+        (score, apples, snake.deaths(), snake.length(), head)
+    }
+
+    /// What the game looked like after each of its ticks, as it was played.
+    fn play_live() -> Vec<Outcome> {
+        let mut outcomes = Vec::new();
+        let mut simulation = simulation(SnakeGameScene::with_config(config()));
+        let interval = simulation.config().tick_interval();
+        let mut inputs = inputs().into_iter().peekable();
+        while simulation.ticks() < TICKS {
+            while let Some(input) = inputs.next_if(|input| input.tick <= simulation.ticks()) {
+                simulation.input(input.game_data);
+            }
+            simulation.tick(interval);
+            outcomes.push(simulation.with_scene(outcome).unwrap());
+        }
+        outcomes
+    }
+
+    /// The tick the replay was at and what it showed after each of the engine's ticks,
+    /// seeking back once it gets to each tick of `seeks`.
+    fn play_replay(speed: u32, seeks: &[u64]) -> Vec<(u64, Outcome)> {
+        let replay = Replay {
+            config: config(),
+            inputs: inputs(),
+            events: Vec::new(),
+        };
+        let mut simulation = simulation(ReplayScene::new(replay));
+        let interval = simulation.config().tick_interval();
+        while simulation
+            .with_scene(|scene: &ReplayScene, _| scene.speed)
+            .unwrap()
+            < speed
+        {
+            simulation.input(GameData {
+                action: Action::Command('f'),
+            });
+        }
+        let mut frames = Vec::new();
+        let mut seeks = seeks.iter().peekable();
+        while !simulation
+            .with_scene(|scene: &ReplayScene, _| scene.ended())
+            .unwrap()
+        {
+            let tick = simulation
+                .with_scene(|scene: &ReplayScene, _| scene.tick())
+                .unwrap();
+            if seeks.next_if(|seek| **seek <= tick).is_some() {
+                simulation.input(GameData {
+                    action: Action::MoveLeft,
+                });
+            }
+            simulation.tick(interval);
+            frames.push(
+                simulation
+                    .with_scene(|replay: &ReplayScene, world| {
+                        (replay.tick(), outcome(replay.scene(), world))
+                    })
+                    .unwrap(),
+            );
+        }
+        frames
+    }
+
+    #[test]
+    fn replays_the_game_as_played_at_any_speed() {
+        let live = play_live();
+        assert!(
+            live[live.len() - 1].2 > 0,
+            "the inputs should make the snake crash"
+        );
+        for speed in [1, 4] {
+            let frames = play_replay(speed, &[]);
+            assert_eq!(
+                frames.last().map(|(tick, _)| *tick),
+                Some(TICKS),
+                "x{}",
+                speed
+            );
+            for (tick, shown) in frames {
+                assert_eq!(
+                    shown,
+                    live[tick as usize - 1],
+                    "x{} at tick {}",
+                    speed,
+                    tick
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn seeking_back_replays_the_same_ticks() {
+        let live = play_live();
+        let frames = play_replay(1, &[600, 1200, 2500]);
+        assert_eq!(frames.len() as u64, TICKS + 3 * SEEK_TICKS);
+        for (tick, shown) in frames {
+            assert_eq!(shown, live[tick as usize - 1], "at tick {}", tick);
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn write_errors_are_reported_when_the_game_ends() {
+        let mut writer = ReplayWriter::create("/dev/full", &config()).unwrap();
+        for tick in 0..2000 {
+            writer.record(
+                tick,
+                GameData {
+                    action: Action::MoveUp,
+                },
+            );
+        }
+        assert!(writer.error.is_some());
+        assert_eq!(
+            writer.finish(),
+            Err("/dev/full: No space left on device (os error 28)".to_string())
+        );
+    }
+
+    #[test]
+    fn replays_are_read_back_as_written() {
+        let level = Level::parse("name Pillars\nchaser 10,2 120\nmap\n..\n.#\n").unwrap();
+        let config = SnakeGameConfig {
+            difficulty: Difficulty::Hard,
+            wrap: true,
+            level: Some(level),
+            tick_rate: 25,
+            ..config()
+        };
+        let path = std::env::temp_dir().join(format!("snake-replay-{}", std::process::id()));
+        let path = path.to_string_lossy();
+        let mut writer = ReplayWriter::create(&path, &config).unwrap();
+        for (tick, action) in [(0, Action::MoveUp), (12, Action::Restart)] {
+            writer.record(tick, GameData { action });
+        }
+        writer.record_event(12, "Restart");
+        writer.record(
+            40,
+            GameData {
+                action: Action::Quit,
+            },
+        );
+        writer.finish().unwrap();
+        let replay = Replay::load(&path);
+        std::fs::remove_file(&*path).unwrap();
+        let replay = replay.unwrap();
+
+        let read = &replay.config;
+        assert_eq!((read.seed, read.width, read.height), (5, 24, 12));
+        assert_eq!(
+            (read.difficulty, read.wrap, read.tick_rate),
+            (Difficulty::Hard, true, 25)
+        );
+        assert_eq!(read.respawn, config.respawn);
+        let level = read.level.as_ref().unwrap();
+        assert_eq!(
+            (level.name.as_str(), level.walls.as_slice()),
+            ("Pillars", &[(2, 2)][..])
+        );
+        assert_eq!(level.hazards, config.level.as_ref().unwrap().hazards);
+        let inputs: Vec<_> = replay
+            .inputs
+            .iter()
+            .map(|input| (input.tick, input.game_data.action))
+            .collect();
+        assert_eq!(
+            inputs,
+            [
+                (0, Action::MoveUp),
+                (12, Action::Restart),
+                (40, Action::Quit)
+            ]
+        );
+        assert_eq!(replay.events, [(12, String::from("Restart"))]);
+        assert_eq!(replay.last_tick(), 40);
+    }
+
+    #[test]
+    fn bad_entries_are_reported_with_their_line() {
+        let parse = |entries: &str| {
+            Replay::parse(&format!("{}\nseed 1\n{}", REPLAY_HEADER, entries)).unwrap_err()
+        };
+        assert_eq!(
+            Replay::parse("seed 1").unwrap_err(),
+            "not a replay file, expected `snake-replay 1`"
+        );
+        assert_eq!(parse("board 30 x"), "line 3: expected a number");
+        assert_eq!(
+            parse("difficulty silly"),
+            "line 3: unknown difficulty `silly`, expected easy, normal or hard"
+        );
+        assert_eq!(parse("walls 3,3 4"), "line 3: expected walls as `x,y`");
+        assert_eq!(
+            parse("hazard ball 3,3 2,0 100"),
+            "line 3: expected a direction as `dx,dy`, found `2,0`"
+        );
+        assert_eq!(parse("input 4 jump"), "line 3: unknown action `jump`");
+        assert_eq!(parse("input 4"), "line 3: expected an action");
+        assert_eq!(parse("score 12"), "line 3: unknown entry `score`");
+        assert_eq!(
+            parse("board 8 8"),
+            "a 8x8 board is not supported, width must be 20 to 200 and height 10 to 100"
+        );
+    }
+}
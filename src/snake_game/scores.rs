@@ -0,0 +1,180 @@
+use crate::snake_game::snake_game_scene::{Difficulty, SnakeGameConfig};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Entries kept in the table; lower scores are dropped when it is saved.
+const MAX_ENTRIES: usize = 100;
+
+#[derive(Clone, Debug)]
+pub struct ScoreEntry {
+    /// The game's `Score::total`, which the table is ranked by.
+    pub score: u32,
+    pub apples: u16,
+    pub difficulty: Difficulty,
+    pub width: usize,
+    pub height: usize,
+    pub wrap: bool,
+    pub level: String,
+    /// Seconds since the Unix epoch when the game ended.
+    pub timestamp: u64,
+}
+
+impl ScoreEntry {
+    /// An entry for a game that just ended, played with `config`.
+    pub fn new(score: u32, apples: u16, config: &SnakeGameConfig) -> ScoreEntry {
+        ScoreEntry {
+            score,
+            apples,
+            difficulty: config.difficulty,
+            width: config.width,
+            height: config.height,
+            wrap: config.wrap,
+            level: config
+                .level
+                .as_ref()
+                .map(|level| level.name.clone())
+                .unwrap_or_default(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs()),
+        }
+    }
+
+    fn parse(line: &str) -> Option<ScoreEntry> {
+        let mut fields = line.split('\t');
+        let score = fields.next()?.parse().ok()?;
+        let apples = fields.next()?.parse().ok()?;
+        let difficulty = fields.next()?.parse().ok()?;
+        let (width, height) = fields.next()?.split_once('x')?;
+        let wrap = fields.next()? == "wrap";
+        let level = fields.next()?.to_string();
+        let timestamp = fields.next()?.parse().ok()?;
+        Some(ScoreEntry {
+            score,
+            apples,
+            difficulty,
+            width: width.parse().ok()?,
+            height: height.parse().ok()?,
+            wrap,
+            level,
+            timestamp,
+        })
+    }
+}
+
+impl std::fmt::Display for ScoreEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}x{}\t{}\t{}\t{}",
+            self.score,
+            self.apples,
+            self.difficulty,
+            self.width,
+            self.height,
+            if self.wrap { "wrap" } else { "walls" },
+            self.level,
+            self.timestamp
+        )
+    }
+}
+
+/// The high-score table, stored as one tab-separated line per game.
+#[derive(Clone, Debug, Default)]
+pub struct HighScores {
+    entries: Vec<ScoreEntry>,
+}
+
+impl HighScores {
+    /// `$XDG_DATA_HOME/snake/scores`, or `~/.local/share/snake/scores`.
+    pub fn default_path() -> Option<PathBuf> {
+        let data_home = match std::env::var_os("XDG_DATA_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(std::env::var_os("HOME")?).join(".local/share"),
+        };
+        Some(data_home.join("snake").join("scores"))
+    }
+
+    /// Reads the table at `path`. A missing file is an empty table, unreadable lines are
+    /// skipped.
+    pub fn load(path: &Path) -> Result<HighScores, String> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("{}: {}", path.display(), e)),
+        };
+        let mut scores = HighScores {
+            entries: text.lines().filter_map(ScoreEntry::parse).collect(),
+        };
+        scores.sort();
+        Ok(scores)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let error = |e: std::io::Error| format!("{}: {}", path.display(), e);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(error)?;
+        }
+        let text: String = self
+            .entries
+            .iter()
+            .take(MAX_ENTRIES)
+            .map(|entry| format!("{}\n", entry))
+            .collect();
+        std::fs::write(path, text).map_err(error)
+    }
+
+    pub fn add(&mut self, entry: ScoreEntry) {
+        self.entries.push(entry);
+        self.sort();
+    }
+
+    /// The best `n` games, best first.
+    pub fn top(&self, n: usize) -> &[ScoreEntry] {
+        &self.entries[..n.min(self.entries.len())]
+    }
+
+    /// Best score first; ties go to the earlier game.
+    fn sort(&mut self) {
+        self.entries
+            .sort_by_key(|entry| (std::cmp::Reverse(entry.score), entry.timestamp));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u32, apples: u16, timestamp: u64) -> ScoreEntry {
+        ScoreEntry {
+            timestamp,
+            ..ScoreEntry::new(score, apples, &SnakeGameConfig::default())
+        }
+    }
+
+    #[test]
+    fn games_are_ranked_by_score() {
+        let mut scores = HighScores::default();
+        scores.add(entry(120, 12, 1));
+        scores.add(entry(300, 9, 2));
+        scores.add(entry(120, 15, 0));
+        let ranked: Vec<_> = scores
+            .top(5)
+            .iter()
+            .map(|entry| (entry.score, entry.apples))
+            .collect();
+        assert_eq!(ranked, [(300, 9), (120, 15), (120, 12)]);
+    }
+
+    #[test]
+    fn entries_are_read_back_as_written() {
+        let line = entry(450, 31, 1_700_000_000).to_string();
+        assert_eq!(line, "450\t31\tnormal\t58x19\twalls\t\t1700000000");
+        let read = ScoreEntry::parse(&line).unwrap();
+        assert_eq!(
+            (read.score, read.apples, read.timestamp),
+            (450, 31, 1_700_000_000)
+        );
+        assert!(ScoreEntry::parse("31\tnormal\t58x19\twalls\t\t1700000000").is_none());
+    }
+}
@@ -0,0 +1,214 @@
+use crate::snake_game::food::FoodKind;
+use std::time::Duration;
+
+/// Eating again within this long of the last food keeps a combo going.
+pub const COMBO_WINDOW: Duration = Duration::from_secs(3);
+
+/// Highest combo multiplier.
+pub const MAX_COMBO: u32 = 5;
+
+/// Points taken off for each life lost.
+pub const LOST_LIFE_PENALTY: u32 = 50;
+
+/// Cells per second at which food is worth its base points, the normal difficulty's speed.
+const REFERENCE_SPEED: f64 = 10.0;
+
+/// Points each apple of a bonus is worth.
+const BONUS_APPLE_POINTS: u32 = 10;
+
+/// What the scene tells the score about.
+#[derive(Copy, Clone, Debug)]
+pub enum ScoreEvent {
+    /// The snake ate food while moving at `speed` cells per second.
+    Ate {
+        kind: FoodKind,
+        speed: f64,
+    },
+    /// The snake reached a bonus worth `apples` while moving at `speed` cells per second.
+    Bonus {
+        apples: u16,
+        speed: f64,
+    },
+    LostLife,
+}
+
+/// Where the points of a game came from.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScoreBreakdown {
+    /// Base points of everything eaten.
+    pub food: u32,
+    /// Points won or lost by eating faster or slower than `REFERENCE_SPEED`.
+    pub speed: i64,
+    /// Extra points from combos.
+    pub combo: u32,
+    /// Points lost with lives.
+    pub penalty: u32,
+    pub best_combo: u32,
+}
+
+impl ScoreBreakdown {
+    /// The score, which never goes below zero.
+    pub fn total(&self) -> u32 {
+        let total =
+            i64::from(self.food) + self.speed + i64::from(self.combo) - i64::from(self.penalty);
+        total.max(0) as u32
+    }
+}
+
+/// Points a food is worth before the speed and combo multipliers.
+pub fn food_points(kind: FoodKind) -> u32 {
+    match kind {
+        FoodKind::Apple => 10,
+        FoodKind::GoldenApple => 50,
+        FoodKind::ShrinkingBerry => 15,
+        FoodKind::Heart => 25,
+        FoodKind::SpeedPepper
+        | FoodKind::Shield
+        | FoodKind::Ghost
+        | FoodKind::Snail
+        | FoodKind::Magnet => 20,
+    }
+}
+
+/// Keeps the score of a game from the events the scene feeds it.
+///
+/// Food is worth its points times the snake's speed relative to `REFERENCE_SPEED`, times
+/// the combo: one more for each food eaten within `COMBO_WINDOW` of the last, up to
+/// `MAX_COMBO`.
+#[derive(Clone, Debug, Default)]
+pub struct Score {
+    breakdown: ScoreBreakdown,
+    combo: u32,
+    /// Time left to keep the combo going.
+    combo_time: Duration,
+}
+
+impl Score {
+    pub fn record(&mut self, event: ScoreEvent) {
+        match event {
+            ScoreEvent::Ate { kind, speed } => self.eat(food_points(kind), speed),
+            ScoreEvent::Bonus { apples, speed } => {
+                self.eat(u32::from(apples) * BONUS_APPLE_POINTS, speed)
+            }
+            ScoreEvent::LostLife => {
+                self.breakdown.penalty += LOST_LIFE_PENALTY;
+                self.combo = 0;
+                self.combo_time = Duration::ZERO;
+            }
+        }
+    }
+
+    fn eat(&mut self, points: u32, speed: f64) {
+        self.combo = if self.combo_time.is_zero() {
+            1
+        } else {
+            (self.combo + 1).min(MAX_COMBO)
+        };
+        self.combo_time = COMBO_WINDOW;
+        let breakdown = &mut self.breakdown;
+        breakdown.best_combo = breakdown.best_combo.max(self.combo);
+        breakdown.food += points;
+        let speed_points = (f64::from(points) * (speed / REFERENCE_SPEED - 1.0)).round() as i64;
+        breakdown.speed += speed_points;
+        breakdown.combo += (points as i64 + speed_points).max(0) as u32 * (self.combo - 1);
+    }
+
+    /// Counts `interval` off the time left to keep the combo going.
+    pub fn tick(&mut self, interval: Duration) {
+        self.combo_time = self.combo_time.saturating_sub(interval);
+        if self.combo_time.is_zero() {
+            self.combo = 0;
+        }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.breakdown.total()
+    }
+
+    pub fn breakdown(&self) -> ScoreBreakdown {
+        self.breakdown
+    }
+
+    /// Multiplier the last food got, while the combo is going, or 0.
+    pub fn combo(&self) -> u32 {
+        self.combo
+    }
+
+    pub fn reset(&mut self) {
+        *self = Score::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apple(speed: f64) -> ScoreEvent {
+        ScoreEvent::Ate {
+            kind: FoodKind::Apple,
+            speed,
+        }
+    }
+
+    fn eat_every(score: &mut Score, events: &[ScoreEvent], interval: Duration) {
+        for event in events {
+            score.record(*event);
+            score.tick(interval);
+        }
+    }
+
+    #[test]
+    fn food_is_worth_more_the_faster_the_snake() {
+        let mut score = Score::default();
+        eat_every(
+            &mut score,
+            &[apple(10.0), apple(15.0), apple(5.0)],
+            COMBO_WINDOW,
+        );
+        let breakdown = score.breakdown();
+        assert_eq!(
+            (breakdown.food, breakdown.speed, breakdown.combo),
+            (30, 0, 0)
+        );
+        assert_eq!(score.total(), 30);
+        score.record(ScoreEvent::Bonus {
+            apples: 4,
+            speed: 10.0,
+        });
+        assert_eq!(score.total(), 70);
+    }
+
+    #[test]
+    fn combos_multiply_food_eaten_in_a_row() {
+        let mut score = Score::default();
+        let second = Duration::from_secs(1);
+        eat_every(&mut score, &[apple(10.0); 7], second);
+        assert_eq!(score.combo(), MAX_COMBO);
+        let breakdown = score.breakdown();
+        assert_eq!(breakdown.combo, 10 * (1 + 2 + 3 + 4 + 4 + 4));
+        assert_eq!(breakdown.best_combo, MAX_COMBO);
+        score.tick(COMBO_WINDOW);
+        assert_eq!(score.combo(), 0);
+        eat_every(&mut score, &[apple(10.0)], second);
+        assert_eq!(score.combo(), 1);
+        assert_eq!(score.total(), 80 + 180);
+    }
+
+    #[test]
+    fn lost_lives_cost_points_and_the_combo() {
+        let mut score = Score::default();
+        eat_every(&mut score, &[apple(10.0), apple(10.0)], Duration::ZERO);
+        score.record(ScoreEvent::LostLife);
+        assert_eq!(score.combo(), 0);
+        assert_eq!(score.breakdown().penalty, LOST_LIFE_PENALTY);
+        assert_eq!(score.total(), 0);
+        score.record(ScoreEvent::Ate {
+            kind: FoodKind::GoldenApple,
+            speed: 10.0,
+        });
+        assert_eq!(score.combo(), 1);
+        assert_eq!(score.total(), 20 + 10 + 50 - LOST_LIFE_PENALTY);
+        score.reset();
+        assert_eq!(score.breakdown(), ScoreBreakdown::default());
+    }
+}
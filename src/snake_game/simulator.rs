@@ -0,0 +1,185 @@
+use crate::snake_game::bots;
+use crate::snake_game::snake::DeathCause;
+use crate::snake_game::snake_env::{EnvConfig, ObservationKind, SnakeEnv};
+use crate::snake_game::snake_game_scene::SnakeGameConfig;
+use std::thread;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    /// Board, difficulty and level every game is played on.
+    pub game: SnakeGameConfig,
+    pub games: u64,
+    pub bot: String,
+    pub seed: u64,
+    pub threads: usize,
+}
+
+/// Outcome of a single game, which lasts until the snake loses its first life.
+#[derive(Copy, Clone, Debug)]
+pub struct GameResult {
+    pub apples: u16,
+    pub ticks: u64,
+    pub death: Option<DeathCause>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SimulationStats {
+    pub games: u64,
+    pub mean_apples: f64,
+    pub max_apples: u16,
+    pub mean_ticks: f64,
+    pub max_ticks: u64,
+    pub wall_deaths: u64,
+    pub self_deaths: u64,
+    pub hazard_deaths: u64,
+    /// Games that reached the step limit without dying.
+    pub timeouts: u64,
+}
+
+/// Plays `config.games` games with the named bot, spread over `config.threads` threads.
+///
+/// Game `i` always uses seed `config.seed + i`, so the results don't depend on how many
+/// threads ran them.
+pub fn simulate(config: &SimulationConfig) -> Result<SimulationStats, String> {
+    if bots::bot_by_name(&config.bot, 0).is_none() {
+        return Err(format!(
+            "unknown bot `{}`, expected one of: {}",
+            config.bot,
+            bots::BOT_NAMES.join(", ")
+        ));
+    }
+    let threads = config.threads.max(1) as u64;
+    let results = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                scope.spawn(move || run_games(config, (t..config.games).step_by(threads as usize)))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    Ok(SimulationStats::from_results(&results))
+}
+
+fn run_games(config: &SimulationConfig, games: impl Iterator<Item = u64>) -> Vec<GameResult> {
+    let mut env = SnakeEnv::new(EnvConfig {
+        game: config.game.clone(),
+        observation: ObservationKind::Features,
+        ..EnvConfig::default()
+    });
+    games
+        .map(|game| {
+            let seed = config.seed.wrapping_add(game);
+            let mut bot = bots::bot_by_name(&config.bot, seed).unwrap();
+            let mut observation = env.reset(seed);
+            loop {
+                let (next, _reward, done, info) = env.step(bot.act(&observation));
+                observation = next;
+                if done {
+                    return GameResult {
+                        apples: info.apples,
+                        ticks: info.ticks,
+                        death: info.death,
+                    };
+                }
+            }
+        })
+        .collect()
+}
+
+impl SimulationStats {
+    pub fn from_results(results: &[GameResult]) -> SimulationStats {
+        let mut stats = SimulationStats {
+            games: results.len() as u64,
+            ..SimulationStats::default()
+        };
+        if results.is_empty() {
+            return stats;
+        }
+        let mut apples = 0u64;
+        let mut ticks = 0u64;
+        for result in results {
+            apples += u64::from(result.apples);
+            ticks += result.ticks;
+            stats.max_apples = stats.max_apples.max(result.apples);
+            stats.max_ticks = stats.max_ticks.max(result.ticks);
+            match result.death {
+                Some(DeathCause::Wall) => stats.wall_deaths += 1,
+                Some(DeathCause::SelfCollision) => stats.self_deaths += 1,
+                Some(DeathCause::Hazard) => stats.hazard_deaths += 1,
+                None => stats.timeouts += 1,
+            }
+        }
+        stats.mean_apples = apples as f64 / stats.games as f64;
+        stats.mean_ticks = ticks as f64 / stats.games as f64;
+        stats
+    }
+
+    pub fn format(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Csv => format!(
+                "games,mean_apples,max_apples,mean_ticks,max_ticks,wall_deaths,self_deaths,hazard_deaths,timeouts\n\
+                 {},{:.3},{},{:.3},{},{},{},{},{}",
+                self.games,
+                self.mean_apples,
+                self.max_apples,
+                self.mean_ticks,
+                self.max_ticks,
+                self.wall_deaths,
+                self.self_deaths,
+                self.hazard_deaths,
+                self.timeouts
+            ),
+            OutputFormat::Json => format!(
+                "{{\"games\":{},\"mean_apples\":{:.3},\"max_apples\":{},\"mean_ticks\":{:.3},\"max_ticks\":{},\"deaths\":{{\"wall\":{},\"self\":{},\"hazard\":{}}},\"timeouts\":{}}}",
+                self.games,
+                self.mean_apples,
+                self.max_apples,
+                self.mean_ticks,
+                self.max_ticks,
+                self.wall_deaths,
+                self.self_deaths,
+                self.hazard_deaths,
+                self.timeouts
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(threads: usize) -> SimulationConfig {
+        SimulationConfig {
+            game: SnakeGameConfig {
+                width: 20,
+                height: 12,
+                ..SnakeGameConfig::default()
+            },
+            games: 12,
+            bot: String::from("greedy"),
+            seed: 1,
+            threads,
+        }
+    }
+
+    #[test]
+    fn results_do_not_depend_on_threads() {
+        let one = simulate(&config(1)).unwrap().format(OutputFormat::Csv);
+        for threads in [2, 5] {
+            let many = simulate(&config(threads))
+                .unwrap()
+                .format(OutputFormat::Csv);
+            assert_eq!(one, many, "{} threads", threads);
+        }
+    }
+}
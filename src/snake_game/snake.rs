@@ -0,0 +1,531 @@
+use crate::ecs::{Collider, Entity, Glyph, Position, System, Velocity};
+use crate::game_engine::{Action, DrawLayer};
+use crate::game_utils::{Direction, Speed};
+use crate::snake_game::animations;
+use crate::snake_game::board::Board;
+use crate::snake_game::effects::{Effect, Effects};
+use crate::snake_game::snake_game_scene::{SnakeGameSceneActions, SnakeWorld, SNAKE_LAYER};
+use crate::snake_game::snake_parts::SnakeBody;
+use std::fmt::{Error, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Parts the snake keeps when it shrinks, head included.
+const MIN_LENGTH: usize = 2;
+
+/// Time the snake blinks for once it is back on its start cell after losing a life.
+const LOST_LIFE_BLINK: Duration = Duration::from_secs(1);
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DeathCause {
+    Wall,
+    SelfCollision,
+    Hazard,
+}
+
+impl std::fmt::Display for DeathCause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            DeathCause::Wall => write!(f, "wall"),
+            DeathCause::SelfCollision => write!(f, "self"),
+            DeathCause::Hazard => write!(f, "hazard"),
+        }
+    }
+}
+
+/// The snake's component. The snake is an entity whose `Position` and `Velocity` are its
+/// head's, and whose `Collider` takes every cell of its body. Each part behind the head is
+/// an entity of its own, with a `Position`, a `SnakeBody` and a `Glyph`.
+pub struct Snake {
+    board: Arc<Board>,
+    /// Every body part, head first. The head is the snake's own entity.
+    body: Vec<Entity>,
+    speed: Speed,
+    effects: Effects,
+    max_lives: u16,
+    lives: u16,
+    apples: u16,
+    deaths: u32,
+    last_death: Option<DeathCause>,
+    games_over: u32,
+    last_game_apples: u16,
+    /// Holds still, e.g. while the game counts down to its start.
+    held: bool,
+    /// Time left it can't die in, after it came back from losing a life.
+    invulnerable: Duration,
+}
+
+impl Snake {
+    /// Adds a snake to `world` on the board's start cell, with four parts behind its head.
+    pub fn spawn(world: &mut SnakeWorld, board: Arc<Board>, speed: Speed, lives: u16) -> Entity {
+        let (x, y) = board.start();
+        let snake = world.spawn();
+        let components = world.components_mut();
+        components.insert(
+            snake,
+            Position {
+                x: x as f64,
+                y: y as f64,
+            },
+        );
+        components.insert(snake, Velocity::new(Direction::right(), speed));
+        components.insert(
+            snake,
+            Snake {
+                board,
+                body: vec![snake],
+                speed,
+                effects: Effects::default(),
+                max_lives: lives,
+                lives,
+                apples: 0,
+                deaths: 0,
+                last_death: None,
+                games_over: 0,
+                last_game_apples: 0,
+                held: false,
+                invulnerable: Duration::ZERO,
+            },
+        );
+        for _ in 0..4 {
+            Self::add_body(world, snake);
+        }
+        Self::refresh(world, snake);
+        snake
+    }
+
+    pub fn apples(&self) -> u16 {
+        self.apples
+    }
+
+    pub fn lives(&self) -> u16 {
+        self.lives
+    }
+
+    pub fn speed(&self) -> Speed {
+        self.speed
+    }
+
+    /// Cells per second the snake moves at now, effects included.
+    pub fn current_speed(&self) -> f64 {
+        self.speed.get_speed_steps_per_second() * self.effects.speed_factor()
+    }
+
+    /// Status effects active on the snake.
+    pub fn effects(&self) -> Effects {
+        self.effects.clone()
+    }
+
+    pub fn has_effect(&self, effect: Effect) -> bool {
+        self.effects.is_active(effect)
+    }
+
+    /// Number of lives lost since the snake was created.
+    pub fn deaths(&self) -> u32 {
+        self.deaths
+    }
+
+    pub fn last_death(&self) -> Option<DeathCause> {
+        self.last_death
+    }
+
+    /// Number of games that ended because the snake lost its last life.
+    pub fn games_over(&self) -> u32 {
+        self.games_over
+    }
+
+    /// Apples eaten in the last game that ended with a game over.
+    pub fn last_game_apples(&self) -> u16 {
+        self.last_game_apples
+    }
+
+    /// Whether nothing can kill the snake now: it is held still, or came back from a lost
+    /// life a short while ago.
+    pub fn invulnerable(&self) -> bool {
+        self.held || !self.invulnerable.is_zero()
+    }
+
+    /// Time left the snake can't die in after coming back from a lost life, if any.
+    pub fn invulnerable_for(&self) -> Option<Duration> {
+        Some(self.invulnerable).filter(|remaining| !remaining.is_zero())
+    }
+
+    pub fn head(&self, world: &SnakeWorld) -> Position {
+        Self::position(world, self.body[0])
+    }
+
+    pub fn direction(&self, world: &SnakeWorld) -> Direction {
+        self.part_direction(world, 0)
+    }
+
+    /// Number of body parts, head included.
+    pub fn length(&self) -> usize {
+        self.body.len()
+    }
+
+    /// Positions of every body part, head first.
+    pub fn segments(&self, world: &SnakeWorld) -> Vec<Position> {
+        self.body
+            .iter()
+            .map(|part| Self::position(world, *part))
+            .collect()
+    }
+
+    fn position(world: &SnakeWorld, part: Entity) -> Position {
+        *world
+            .components()
+            .get::<Position>(part)
+            .expect("Snake part without a position")
+    }
+
+    /// Direction the part at `index` last moved in. The head's is the way it is heading.
+    fn part_direction(&self, world: &SnakeWorld, index: usize) -> Direction {
+        let components = world.components();
+        let direction = match index {
+            0 => components
+                .get::<Velocity>(self.body[0])
+                .map(|velocity| velocity.direction),
+            _ => components
+                .get::<SnakeBody>(self.body[index])
+                .map(|part| part.direction),
+        };
+        direction.expect("Snake part without a direction")
+    }
+
+    fn get(world: &SnakeWorld, snake: Entity) -> &Snake {
+        world.components().get(snake).expect("No snake")
+    }
+
+    fn get_mut(world: &mut SnakeWorld, snake: Entity) -> &mut Snake {
+        world.components_mut().get_mut(snake).expect("No snake")
+    }
+
+    /// The glyph set part for a body part moving `direction`, followed by a part moving
+    /// `behind`: a straight piece, a corner where the body turns, or the tail.
+    fn body_part(direction: Direction, behind: Option<Direction>) -> &'static str {
+        let behind = match behind {
+            Some(behind) => behind.opposite(),
+            None => return "snake_tail",
+        };
+        let (vertical, horizontal) = match (direction.is_horizontal(), behind.is_horizontal()) {
+            (true, true) => return "snake_body_horizontal",
+            (false, false) => return "snake_body_vertical",
+            (true, false) => (behind, direction),
+            (false, true) => (direction, behind),
+        };
+        match (vertical.name(), horizontal.name()) {
+            ("up", "left") => "snake_corner_up_left",
+            ("up", _) => "snake_corner_up_right",
+            ("down", "left") => "snake_corner_down_left",
+            _ => "snake_corner_down_right",
+        }
+    }
+
+    fn head_part(direction: Direction) -> &'static str {
+        match direction.name() {
+            "up" => "snake_head_up",
+            "down" => "snake_head_down",
+            "left" => "snake_head_left",
+            _ => "snake_head_right",
+        }
+    }
+
+    /// Grows a part behind the tail.
+    fn add_body(world: &mut SnakeWorld, snake: Entity) {
+        let this = Self::get(world, snake);
+        let tail = this.body.len() - 1;
+        let direction = this.part_direction(world, tail);
+        let position = Self::position(world, this.body[tail]);
+        let position = this.board.wrap(position + direction * -1f64);
+        let part = world.spawn();
+        let components = world.components_mut();
+        components.insert(part, position);
+        components.insert(part, SnakeBody { direction });
+        Self::get_mut(world, snake).body.push(part);
+    }
+
+    /// Points the parts' glyphs the way the body goes and moves the collider onto it.
+    fn refresh(world: &mut SnakeWorld, snake: Entity) {
+        let this = Self::get(world, snake);
+        let body = this.body.clone();
+        let directions: Vec<_> = (0..body.len())
+            .map(|index| this.part_direction(world, index))
+            .collect();
+        let cells = this
+            .segments(world)
+            .iter()
+            .map(Position::get_screen_coordinates)
+            .collect();
+        let components = world.components_mut();
+        for (index, part) in body.iter().enumerate() {
+            let glyph = if index == 0 {
+                let part = Self::head_part(directions[0]);
+                Glyph::styled(part, "snake_head", DrawLayer::Actors)
+            } else {
+                let part = Self::body_part(directions[index], directions.get(index + 1).copied());
+                Glyph::styled(part, "snake_body", DrawLayer::Actors)
+            };
+            components.insert(*part, glyph);
+        }
+        // Runs into things with its head. Running into its own body is checked as it moves.
+        components.insert(
+            snake,
+            Collider {
+                layer: SNAKE_LAYER,
+                cells,
+                moving: true,
+            },
+        );
+    }
+
+    /// Moves every part behind the head to where the part in front of it was, once
+    /// `Movement` moved the head. Each one takes the direction the part in front moved in,
+    /// which comes down to the tail taking the head's old cell.
+    fn follow_head(world: &mut SnakeWorld, snake: Entity) {
+        let direction = Self::get(world, snake).direction(world);
+        let this = Self::get_mut(world, snake);
+        let board = Arc::clone(&this.board);
+        let tail = this.body.pop().expect("Snake without a body");
+        this.body.insert(1, tail);
+        let (behind, last) = (this.body.get(2).copied(), this.body[this.body.len() - 1]);
+        let components = world.components_mut();
+        let head = components.get_mut::<Position>(snake).expect("No head");
+        *head = board.wrap(*head);
+        let (head, neck) = (*head, board.wrap(*head + direction * -1f64));
+        components.insert(tail, neck);
+        let behind = behind.and_then(|part| components.get::<SnakeBody>(part));
+        let part = Self::body_part(direction, behind.map(|part| part.direction));
+        components.insert(tail, SnakeBody { direction });
+        components.insert(tail, Glyph::styled(part, "snake_body", DrawLayer::Actors));
+        if last != tail {
+            components.insert(
+                last,
+                Glyph::styled("snake_tail", "snake_body", DrawLayer::Actors),
+            );
+        }
+        if let Some(collider) = components.get_mut::<Collider>(snake) {
+            collider.cells.pop();
+            collider.cells.insert(1, neck.get_screen_coordinates());
+            collider.cells[0] = head.get_screen_coordinates();
+        }
+        if Self::get(world, snake).check_collision(world, snake) {
+            Self::lose_life(world, snake, DeathCause::SelfCollision);
+        }
+    }
+
+    fn check_collision(&self, world: &SnakeWorld, snake: Entity) -> bool {
+        if self.has_effect(Effect::Invincible) || self.invulnerable() {
+            return false;
+        }
+        match world.components().get::<Collider>(snake) {
+            Some(collider) => collider.cells[1..].contains(&collider.cells[0]),
+            None => false,
+        }
+    }
+
+    fn reset(world: &mut SnakeWorld, snake: Entity) {
+        let this = Self::get_mut(world, snake);
+        let (x, y) = this.board.start();
+        let removed = this.body.split_off(1);
+        this.effects.clear();
+        this.invulnerable = Duration::ZERO;
+        for part in removed {
+            world.despawn(part);
+        }
+        let components = world.components_mut();
+        components.insert(
+            snake,
+            Position {
+                x: x as f64,
+                y: y as f64,
+            },
+        );
+        if let Some(velocity) = components.get_mut::<Velocity>(snake) {
+            velocity.stop();
+        }
+        for _ in 0..4 {
+            Self::add_body(world, snake);
+        }
+        Self::refresh(world, snake);
+    }
+
+    fn eat_apple(world: &mut SnakeWorld, snake: Entity) {
+        Self::add_body(world, snake);
+        Self::get_mut(world, snake).apples += 1;
+        Self::refresh(world, snake);
+    }
+
+    /// Cuts `segments` parts off the tail, leaving at least `MIN_LENGTH`.
+    fn shrink(world: &mut SnakeWorld, snake: Entity, segments: usize) {
+        let body = &mut Self::get_mut(world, snake).body;
+        let length = body.len().saturating_sub(segments).max(MIN_LENGTH);
+        let removed = body.split_off(length.min(body.len()));
+        for part in removed {
+            world.despawn(part);
+        }
+        Self::refresh(world, snake);
+    }
+
+    fn lose_life(world: &mut SnakeWorld, snake: Entity, cause: DeathCause) {
+        Self::crumble(world, snake);
+        let this = Self::get_mut(world, snake);
+        this.deaths += 1;
+        this.last_death = Some(cause);
+        if this.lives > 1 {
+            this.lives -= 1;
+            Self::reset(world, snake);
+            let body = Self::get(world, snake).body.clone();
+            animations::blink(world, &body, LOST_LIFE_BLINK);
+            return;
+        }
+        this.games_over += 1;
+        this.last_game_apples = this.apples;
+        Self::restart(world, snake);
+    }
+
+    /// Leaves the body behind to crumble where the snake died.
+    fn crumble(world: &mut SnakeWorld, snake: Entity) {
+        let components = world.components();
+        let parts: Vec<_> = Self::get(world, snake)
+            .body
+            .iter()
+            .filter_map(|part| {
+                let position = components.get::<Position>(*part)?;
+                Some((*position, *components.get::<Glyph>(*part)?))
+            })
+            .collect();
+        animations::crumble(world, &parts);
+    }
+
+    fn restart(world: &mut SnakeWorld, snake: Entity) {
+        let this = Self::get_mut(world, snake);
+        this.lives = this.max_lives;
+        this.apples = 0;
+        this.last_death = None;
+        this.held = false;
+        if let Some(velocity) = world.components_mut().get_mut::<Velocity>(snake) {
+            velocity.direction = Direction::right();
+        }
+        Self::reset(world, snake);
+    }
+
+    /// Stops the snake where it is until it is let go. It can still turn meanwhile.
+    pub fn hold(world: &mut SnakeWorld, snake: Entity, held: bool) {
+        Self::get_mut(world, snake).held = held;
+    }
+
+    /// Makes the snake unable to die for `duration`, blinking meanwhile.
+    pub fn protect(world: &mut SnakeWorld, snake: Entity, duration: Duration) {
+        let this = Self::get_mut(world, snake);
+        this.invulnerable = duration;
+        let body = this.body.clone();
+        animations::blink(world, &body, duration);
+    }
+
+    /// Turns the head the way the player pressed, unless it would turn back on itself.
+    pub fn turn(world: &mut SnakeWorld, snake: Entity, action: Action) {
+        let (new_direction, opposite_direction) = match action {
+            Action::MoveDown => (Direction::down(), Direction::up()),
+            Action::MoveUp => (Direction::up(), Direction::down()),
+            Action::MoveRight => (Direction::right(), Direction::left()),
+            Action::MoveLeft => (Direction::left(), Direction::right()),
+            _ => return,
+        };
+        let velocity = match world.components_mut().get_mut::<Velocity>(snake) {
+            Some(velocity) => velocity,
+            None => return,
+        };
+        let body_direction = &mut velocity.direction;
+        if new_direction != *body_direction && opposite_direction != *body_direction {
+            *body_direction = new_direction;
+            // FIX Try to find a way to force move as soon as the action is sent
+            //     Code below doesn't respect speed.
+            // let mut body_position = body.position.lock().unwrap();
+            // *body_position = *body_position + *body_direction * 0.49999f64;
+            let part = Self::head_part(new_direction);
+            let glyph = Glyph::styled(part, "snake_head", DrawLayer::Actors);
+            world.components_mut().insert(snake, glyph);
+        };
+    }
+
+    fn on_event(world: &mut SnakeWorld, snake: Entity, event: SnakeGameSceneActions) {
+        match event {
+            SnakeGameSceneActions::EatApple => Self::eat_apple(world, snake),
+            SnakeGameSceneActions::HitWall => Self::lose_life(world, snake, DeathCause::Wall),
+            SnakeGameSceneActions::Restart => Self::restart(world, snake),
+            SnakeGameSceneActions::BonusApples(apples) => {
+                Self::get_mut(world, snake).apples += apples
+            }
+            SnakeGameSceneActions::Shrink(segments) => Self::shrink(world, snake, segments),
+            SnakeGameSceneActions::PowerUp(effect) => {
+                Self::get_mut(world, snake).effects.add(effect)
+            }
+            SnakeGameSceneActions::ExtraLife => Self::get_mut(world, snake).lives += 1,
+            SnakeGameSceneActions::HitHazard => Self::lose_life(world, snake, DeathCause::Hazard),
+            SnakeGameSceneActions::EatBonus
+            | SnakeGameSceneActions::Pull(_)
+            | SnakeGameSceneActions::Chase(_)
+            | SnakeGameSceneActions::MagnetPull
+            | SnakeGameSceneActions::HideGameOver
+            | SnakeGameSceneActions::Go => (),
+        }
+    }
+}
+
+/// Ticks the effects and the invulnerability on every snake and sets how fast they make it
+/// move, or stops it while it is held. Runs before `Movement`, so a snake moves at the speed
+/// its effects give it in the same tick.
+pub struct SnakeEffects;
+
+impl System<SnakeGameSceneActions> for SnakeEffects {
+    fn run(&mut self, world: &mut SnakeWorld, interval: Duration) {
+        for snake in world.components().entities::<Snake>() {
+            let this = Snake::get_mut(world, snake);
+            let factor = match this.held {
+                true => 0.0,
+                false => {
+                    this.effects.tick(interval);
+                    this.invulnerable = this.invulnerable.saturating_sub(interval);
+                    this.effects.speed_factor()
+                }
+            };
+            if let Some(velocity) = world.components_mut().get_mut::<Velocity>(snake) {
+                velocity.factor = factor;
+            }
+        }
+    }
+}
+
+/// Drags every snake's body after its head once `Movement` moved it, and changes the snakes
+/// as the game's events say.
+pub struct SnakeSystem;
+
+impl System<SnakeGameSceneActions> for SnakeSystem {
+    fn run(&mut self, world: &mut SnakeWorld, _interval: Duration) {
+        for snake in world.components().entities::<Snake>() {
+            let moved = world
+                .components()
+                .get::<Velocity>(snake)
+                .is_some_and(Velocity::moved);
+            if moved {
+                Snake::follow_head(world, snake);
+            }
+        }
+    }
+    fn subscribes(&self, event: &SnakeGameSceneActions) -> bool {
+        !matches!(
+            event,
+            SnakeGameSceneActions::EatBonus
+                | SnakeGameSceneActions::Pull(_)
+                | SnakeGameSceneActions::Chase(_)
+                | SnakeGameSceneActions::MagnetPull
+                | SnakeGameSceneActions::HideGameOver
+                | SnakeGameSceneActions::Go
+        )
+    }
+    fn on_event(&mut self, world: &mut SnakeWorld, event: SnakeGameSceneActions) {
+        for snake in world.components().entities::<Snake>() {
+            Snake::on_event(world, snake, event);
+        }
+    }
+}
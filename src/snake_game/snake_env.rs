@@ -0,0 +1,401 @@
+use crate::game_engine::{Action, Game, GameData, Simulation};
+use crate::game_utils::{Direction, Position};
+use crate::snake_game::board::Board;
+use crate::snake_game::snake::{DeathCause, Snake};
+use crate::snake_game::snake_game_scene::{
+    SnakeGameConfig, SnakeGameScene, SnakeGameSceneActions, SnakeWorld,
+};
+use std::sync::Arc;
+
+/// Discrete action space, indexable by an agent's action id.
+pub const ACTIONS: [Action; 4] = [
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::MoveLeft,
+    Action::MoveRight,
+];
+
+/// Ticks a step runs past the time the snake takes to move a cell before it gives up
+/// waiting for it to move.
+const STEP_MARGIN_TICKS: u64 = 10;
+
+const GRID_CHANNELS: usize = 4;
+const FEATURES: usize = 14;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ObservationKind {
+    /// One-hot `[body, head, apple, wall]` planes of `height x width` of the board.
+    Grid,
+    /// Dangers around the head, heading, apple direction and distance, and length.
+    Features,
+    /// The board as it would be drawn, border included, one `char` per cell.
+    Chars,
+}
+
+#[derive(Clone, Debug)]
+pub enum Observation {
+    Grid {
+        channels: usize,
+        height: usize,
+        width: usize,
+        data: Vec<f32>,
+    },
+    Features(Vec<f32>),
+    Chars(Vec<Vec<char>>),
+}
+
+#[derive(Clone, Debug)]
+pub struct EnvConfig {
+    /// Board, difficulty and level to play on. Its seed is replaced on every `reset`.
+    pub game: SnakeGameConfig,
+    pub observation: ObservationKind,
+    pub apple_reward: f64,
+    pub death_penalty: f64,
+    pub step_reward: f64,
+    /// Ends the episode after this many steps even if the snake is alive.
+    pub max_steps: Option<u64>,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            game: SnakeGameConfig::default(),
+            observation: ObservationKind::Grid,
+            apple_reward: 1.0,
+            death_penalty: -1.0,
+            step_reward: 0.0,
+            max_steps: Some(10_000),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct StepInfo {
+    pub steps: u64,
+    /// Engine ticks since the episode started.
+    pub ticks: u64,
+    pub apples: u16,
+    /// Points the game is worth so far, see `Score`.
+    pub score: u32,
+    pub lives: u16,
+    pub length: usize,
+    pub death: Option<DeathCause>,
+}
+
+/// A gym-style environment around `SnakeGameScene`.
+///
+/// A step runs engine ticks until the snake moved by one cell, or for a few ticks longer
+/// than a cell takes if it doesn't, e.g. while it is held for a countdown. An episode ends
+/// when the snake loses a life or `max_steps` is reached. Everything runs on the calling
+/// thread with a fixed tick, so the same seed and actions always produce the same episode.
+pub struct SnakeEnv {
+    config: EnvConfig,
+    board: Arc<Board>,
+    simulation: Simulation<SnakeGameSceneActions>,
+    steps: u64,
+    ticks: u64,
+    apples: u16,
+    deaths: u32,
+}
+
+impl SnakeEnv {
+    pub fn new(config: EnvConfig) -> SnakeEnv {
+        let scene = SnakeGameScene::with_config(config.game.clone());
+        let board = scene.board();
+        let mut game = Game::new(Box::new(scene));
+        game.set_config(config.game.engine_config());
+        let mut simulation = game.simulation();
+        simulation.load();
+        SnakeEnv {
+            config,
+            board,
+            simulation,
+            steps: 0,
+            ticks: 0,
+            apples: 0,
+            deaths: 0,
+        }
+    }
+
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.simulation
+            .with_scene_mut(|scene: &mut SnakeGameScene, world| scene.reset(world, seed));
+        self.steps = 0;
+        self.ticks = 0;
+        let info = self.info(None);
+        self.apples = info.apples;
+        self.deaths = self.with_snake(|snake, _| snake.deaths());
+        self.observe()
+    }
+
+    pub fn step(&mut self, action: Action) -> (Observation, f64, bool, StepInfo) {
+        self.simulation.input(GameData { action });
+        let (head, speed) =
+            self.with_snake(|snake, world| (snake.head(world), snake.current_speed()));
+        let tick_rate = self.simulation.config().tick_rate as f64;
+        let max_ticks = (tick_rate / speed).ceil() as u64 + STEP_MARGIN_TICKS;
+        let (mut deaths, mut last_death) = (self.deaths, None);
+        for _ in 0..max_ticks {
+            self.simulation
+                .tick(self.simulation.config().tick_interval());
+            self.ticks += 1;
+            let position;
+            (deaths, last_death, position) = self
+                .with_snake(|snake, world| (snake.deaths(), snake.last_death(), snake.head(world)));
+            if deaths != self.deaths || position != head {
+                break;
+            }
+        }
+        self.steps += 1;
+
+        let death = if deaths != self.deaths {
+            self.deaths = deaths;
+            last_death
+        } else {
+            None
+        };
+        let info = self.info(death);
+
+        let mut reward = self.config.step_reward;
+        if death.is_some() {
+            reward += self.config.death_penalty;
+        } else if info.apples > self.apples {
+            reward += self.config.apple_reward * f64::from(info.apples - self.apples);
+        }
+        self.apples = info.apples;
+
+        let truncated = matches!(self.config.max_steps, Some(max) if self.steps >= max);
+        (self.observe(), reward, death.is_some() || truncated, info)
+    }
+
+    pub fn observe(&self) -> Observation {
+        let (segments, direction) =
+            self.with_snake(|snake, world| (snake.segments(world), snake.direction(world)));
+        let apple = self
+            .simulation
+            .with_scene(|scene: &SnakeGameScene, world| scene.food_position(world))
+            .unwrap();
+        match self.config.observation {
+            ObservationKind::Grid => self.grid(&segments, apple),
+            ObservationKind::Features => self.features(&segments, direction, apple),
+            ObservationKind::Chars => self.chars(&segments, direction, apple),
+        }
+    }
+
+    fn info(&self, death: Option<DeathCause>) -> StepInfo {
+        let (apples, lives, length) =
+            self.with_snake(|snake, _| (snake.apples(), snake.lives(), snake.length()));
+        let score = self
+            .simulation
+            .with_scene(|scene: &SnakeGameScene, _| scene.score().total())
+            .unwrap();
+        StepInfo {
+            steps: self.steps,
+            ticks: self.ticks,
+            apples,
+            score,
+            lives,
+            length,
+            death,
+        }
+    }
+
+    fn with_snake<R>(&self, f: impl FnOnce(&Snake, &SnakeWorld) -> R) -> R {
+        self.simulation
+            .with_scene(|scene: &SnakeGameScene, world| f(scene.snake(world), world))
+            .unwrap()
+    }
+
+    /// Zero-based board cell of `position`, if it is inside the game area.
+    fn cell(&self, position: Position) -> Option<(usize, usize)> {
+        let (x, y) = self.board.cell(position);
+        if self.board.contains((x, y)) {
+            Some((x - 1, y - 1))
+        } else {
+            None
+        }
+    }
+
+    fn grid(&self, segments: &[Position], apple: Position) -> Observation {
+        let (width, height) = (self.board.width(), self.board.height());
+        let mut data = vec![0f32; GRID_CHANNELS * width * height];
+        let mut set = |channel: usize, cell: Option<(usize, usize)>| {
+            if let Some((x, y)) = cell {
+                data[(channel * height + y) * width + x] = 1.0;
+            }
+        };
+        for segment in segments.iter().skip(1) {
+            set(0, self.cell(*segment));
+        }
+        set(1, self.cell(segments[0]));
+        set(2, self.cell(apple));
+        for (x, y) in self.board.walls() {
+            set(3, Some((x - 1, y - 1)));
+        }
+        Observation::Grid {
+            channels: GRID_CHANNELS,
+            height,
+            width,
+            data,
+        }
+    }
+
+    fn features(
+        &self,
+        segments: &[Position],
+        direction: Direction,
+        apple: Position,
+    ) -> Observation {
+        let head = segments[0];
+        let (left, right) = Self::sides(direction);
+        let blocked = |offset: Position| {
+            let next = self.board.wrap(head + offset);
+            self.board.blocks(next) || segments[1..].contains(&next)
+        };
+        let flag = |value: bool| if value { 1f32 } else { 0f32 };
+        let (width, height) = (self.board.width() as f64, self.board.height() as f64);
+        let mut features = Vec::with_capacity(FEATURES);
+        features.push(flag(blocked(direction * 1f64)));
+        features.push(flag(blocked(left * 1f64)));
+        features.push(flag(blocked(right * 1f64)));
+        features.push(flag(direction == Direction::up()));
+        features.push(flag(direction == Direction::down()));
+        features.push(flag(direction == Direction::left()));
+        features.push(flag(direction == Direction::right()));
+        features.push(flag(apple.y < head.y));
+        features.push(flag(apple.y > head.y));
+        features.push(flag(apple.x < head.x));
+        features.push(flag(apple.x > head.x));
+        features.push(((apple.x - head.x) / width) as f32);
+        features.push(((apple.y - head.y) / height) as f32);
+        features.push((segments.len() as f64 / (width * height)) as f32);
+        Observation::Features(features)
+    }
+
+    fn chars(&self, segments: &[Position], direction: Direction, apple: Position) -> Observation {
+        let (width, height) = (self.board.width(), self.board.height());
+        let mut rows = vec![vec![' '; width + 2]; height + 2];
+        for (y, row) in rows.iter_mut().enumerate() {
+            for (x, c) in row.iter_mut().enumerate() {
+                if x == 0
+                    || y == 0
+                    || x == width + 1
+                    || y == height + 1
+                    || self.board.is_wall((x, y))
+                {
+                    *c = '#';
+                }
+            }
+        }
+        let mut put = |c: char, cell: Option<(usize, usize)>| {
+            if let Some((x, y)) = cell {
+                rows[y + 1][x + 1] = c;
+            }
+        };
+        put('@', self.cell(apple));
+        for segment in segments.iter().skip(1) {
+            put('o', self.cell(*segment));
+        }
+        put(
+            direction.to_string().chars().next().unwrap(),
+            self.cell(segments[0]),
+        );
+        Observation::Chars(rows)
+    }
+
+    /// Directions to the left and to the right of `direction`.
+    fn sides(direction: Direction) -> (Direction, Direction) {
+        if direction == Direction::up() {
+            (Direction::left(), Direction::right())
+        } else if direction == Direction::left() {
+            (Direction::down(), Direction::up())
+        } else if direction == Direction::down() {
+            (Direction::right(), Direction::left())
+        } else {
+            (Direction::up(), Direction::down())
+        }
+    }
+}
+
+impl Default for SnakeEnv {
+    fn default() -> Self {
+        SnakeEnv::new(EnvConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snake_game::{bots, Difficulty};
+
+    fn features(observation: Observation) -> Vec<f32> {
+        match observation {
+            Observation::Features(features) => features,
+            observation => panic!("expected features, got {:?}", observation),
+        }
+    }
+
+    /// Plays an episode from `seed` with `actions`, or with the greedy bot if there are none,
+    /// and returns the observations and rewards it saw and the actions it took.
+    fn play(env: &mut SnakeEnv, seed: u64, actions: &[Action]) -> (Vec<Vec<f32>>, Vec<Action>) {
+        let mut bot = bots::bot_by_name("greedy", seed).unwrap();
+        let mut observation = env.reset(seed);
+        let mut seen = vec![features(observation.clone())];
+        let mut taken = Vec::new();
+        loop {
+            let action = match actions.get(taken.len()) {
+                Some(action) => *action,
+                None if actions.is_empty() => bot.act(&observation),
+                None => break,
+            };
+            let (next, reward, done, _) = env.step(action);
+            taken.push(action);
+            seen.push(features(next.clone()));
+            seen.push(vec![reward as f32]);
+            observation = next;
+            if done {
+                break;
+            }
+        }
+        (seen, taken)
+    }
+
+    #[test]
+    fn reset_with_the_same_seed_replays_the_episode() {
+        let mut env = SnakeEnv::new(EnvConfig {
+            game: SnakeGameConfig {
+                width: 20,
+                height: 12,
+                ..SnakeGameConfig::default()
+            },
+            observation: ObservationKind::Features,
+            ..EnvConfig::default()
+        });
+        for seed in 0..12 {
+            let (first, actions) = play(&mut env, seed, &[]);
+            let (second, _) = play(&mut env, seed, &actions);
+            assert_eq!(first, second, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn steps_return_while_the_snake_is_held() {
+        let mut env = SnakeEnv::new(EnvConfig {
+            game: SnakeGameConfig {
+                respawn: Some(Difficulty::Normal.respawn()),
+                ..SnakeGameConfig::default()
+            },
+            ..EnvConfig::default()
+        });
+        env.reset(3);
+        let head = env.with_snake(|snake, world| snake.head(world));
+        let (_, reward, done, info) = env.step(Action::Command('x'));
+        assert_eq!((reward, done), (0.0, false));
+        assert_eq!(info.ticks, 5 + STEP_MARGIN_TICKS);
+        assert_eq!(env.with_snake(|snake, world| snake.head(world)), head);
+    }
+}
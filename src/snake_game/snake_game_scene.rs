@@ -0,0 +1,755 @@
+use crate::ecs::{Entity, Lifetimes, Movement};
+use crate::game_engine;
+use crate::game_engine::{Action, EngineConfig, GameData, GameEvent, GameScene, ObjectId, World};
+use crate::game_utils::{Position, Speed};
+use crate::snake_game::animations;
+use crate::snake_game::board::Board;
+use crate::snake_game::effects::Effect;
+use crate::snake_game::food::{BonusFood, Food, FoodSystem};
+use crate::snake_game::hazards;
+use crate::snake_game::level::Level;
+use crate::snake_game::scores::{HighScores, ScoreEntry};
+use crate::snake_game::scoring::{Score, ScoreBreakdown, ScoreEvent};
+use crate::snake_game::snake::{Snake, SnakeEffects, SnakeSystem};
+use crate::snake_game::walls::Walls;
+use crate::timers::TimerId;
+use std::any::Any;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Board sizes a game can be configured with.
+pub const BOARD_WIDTHS: std::ops::RangeInclusive<usize> = 20..=200;
+pub const BOARD_HEIGHTS: std::ops::RangeInclusive<usize> = 10..=100;
+/// Milliseconds a `Respawn` can count down for and keep the snake from dying for.
+pub const COUNTDOWN_TIMES: std::ops::RangeInclusive<usize> = 300..=10_000;
+pub const INVULNERABLE_TIMES: std::ops::RangeInclusive<usize> = 0..=10_000;
+
+/// Time between two cells a magnet pulls food by.
+const MAGNET_PULL_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Collision layers of the scene's objects.
+pub const SNAKE_LAYER: &str = "snake";
+pub const FOOD_LAYER: &str = "food";
+pub const BONUS_LAYER: &str = "bonus";
+pub const WALLS_LAYER: &str = "walls";
+pub const HAZARD_LAYER: &str = "hazard";
+
+/// Time the HUD shows how the last game scored after it ends.
+const GAME_OVER_DISPLAY: Duration = Duration::from_secs(5);
+
+/// Events of the snake game, sent by the scene to its objects.
+#[derive(Copy, Clone, Debug)]
+pub enum SnakeGameSceneActions {
+    EatApple,
+    /// The snake reached the bonus before it vanished.
+    EatBonus,
+    HitWall,
+    Restart,
+    /// Apples added to the count on top of the one eaten.
+    BonusApples(u16),
+    /// Tail segments the snake loses.
+    Shrink(usize),
+    /// A status effect the snake gets for a while.
+    PowerUp(Effect),
+    /// Moves food a cell towards the snake's head.
+    Pull(Position),
+    ExtraLife,
+    /// The snake ran into a moving hazard.
+    HitHazard,
+    /// Tells chasers where the snake's head is.
+    Chase(Position),
+    /// Time for the magnet to pull the food again.
+    MagnetPull,
+    /// Time for the HUD to stop showing how the last game scored.
+    HideGameOver,
+    /// The countdown at the start of a game, or after the snake lost a life, is over.
+    Go,
+}
+
+impl GameEvent for SnakeGameSceneActions {
+    /// Chasing and pulling happen every few ticks and would crowd out everything else, and
+    /// the scene's timers go off again on a replay.
+    fn logged(&self) -> bool {
+        !matches!(
+            self,
+            SnakeGameSceneActions::Chase(_)
+                | SnakeGameSceneActions::Pull(_)
+                | SnakeGameSceneActions::MagnetPull
+                | SnakeGameSceneActions::HideGameOver
+                | SnakeGameSceneActions::Go
+        )
+    }
+}
+
+/// The world of a snake game.
+pub type SnakeWorld = World<SnakeGameSceneActions>;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn speed(&self) -> Speed {
+        match self {
+            Difficulty::Easy => Speed::period_in_milliseconds(150),
+            Difficulty::Normal => Speed::period_in_milliseconds(100),
+            Difficulty::Hard => Speed::period_in_milliseconds(70),
+        }
+    }
+
+    /// Cells per second the snake can move at, sped up by an effect. A tick moves it a cell
+    /// at most, so the game has to tick at least as often.
+    pub fn fastest_speed(&self) -> f64 {
+        self.speed().get_speed_steps_per_second() * Effect::SpeedUp.speed_factor()
+    }
+
+    pub fn lives(&self) -> u16 {
+        match self {
+            Difficulty::Easy => 7,
+            Difficulty::Normal => 5,
+            Difficulty::Hard => 3,
+        }
+    }
+
+    /// How the snake gets back into the game unless the settings say otherwise.
+    pub fn respawn(&self) -> Respawn {
+        let (countdown, invulnerable) = match self {
+            Difficulty::Easy => (3000, 3000),
+            Difficulty::Normal => (3000, 2000),
+            Difficulty::Hard => (1500, 1000),
+        };
+        Respawn {
+            countdown: Duration::from_millis(countdown),
+            invulnerable: Duration::from_millis(invulnerable),
+        }
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Normal => write!(f, "normal"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+impl std::str::FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!(
+                "unknown difficulty `{}`, expected easy, normal or hard",
+                s
+            )),
+        }
+    }
+}
+
+/// How the snake gets ready for a game, and back into it after losing a life: it holds
+/// still while the board counts down, and after a lost life waits for a direction and then
+/// can't die for a while.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Respawn {
+    pub countdown: Duration,
+    pub invulnerable: Duration,
+}
+
+/// Everything that decides how a game of snake plays.
+#[derive(Clone, Debug)]
+pub struct SnakeGameConfig {
+    pub width: usize,
+    pub height: usize,
+    /// Seed for every random event, so a game is reproducible from its inputs.
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    /// Leaving the board on one edge brings the snake back on the opposite one.
+    pub wrap: bool,
+    pub level: Option<Level>,
+    /// High-score table that finished games are added to.
+    pub scores_file: Option<PathBuf>,
+    /// Countdowns and invulnerability for a person playing, or `None` for the snake to
+    /// start right away and come back moving, as bots and headless runs want.
+    pub respawn: Option<Respawn>,
+    /// Ticks per second, one of `game_engine::TICK_RATES`.
+    pub tick_rate: usize,
+}
+
+impl SnakeGameConfig {
+    pub fn engine_config(&self) -> EngineConfig {
+        EngineConfig {
+            board_width: self.width,
+            board_height: self.height,
+            tick_rate: self.tick_rate,
+            ..EngineConfig::default()
+        }
+    }
+
+    pub fn board(&self) -> Board {
+        Board::new(self.width, self.height, self.wrap, self.level.as_ref())
+    }
+
+    /// Checks the board size and tick rate, and that the level, if any, fits the board.
+    pub fn validate(&self) -> Result<(), String> {
+        if !game_engine::TICK_RATES.contains(&self.tick_rate) {
+            return Err(format!(
+                "a tick rate of {} is not supported, it must be {} to {}",
+                self.tick_rate,
+                game_engine::TICK_RATES.start(),
+                game_engine::TICK_RATES.end()
+            ));
+        }
+        let slowest = self.difficulty.fastest_speed().ceil() as usize;
+        if self.tick_rate < slowest {
+            return Err(format!(
+                "a tick rate of {} is too slow for {} difficulty, it must be at least {}",
+                self.tick_rate, self.difficulty, slowest
+            ));
+        }
+        if !BOARD_WIDTHS.contains(&self.width) || !BOARD_HEIGHTS.contains(&self.height) {
+            return Err(format!(
+                "a {}x{} board is not supported, width must be {} to {} and height {} to {}",
+                self.width,
+                self.height,
+                BOARD_WIDTHS.start(),
+                BOARD_WIDTHS.end(),
+                BOARD_HEIGHTS.start(),
+                BOARD_HEIGHTS.end()
+            ));
+        }
+        if let Some(respawn) = self.respawn {
+            let millis = |time: Duration| time.as_millis() as usize;
+            if !COUNTDOWN_TIMES.contains(&millis(respawn.countdown)) {
+                return Err(format!(
+                    "a countdown of {} ms is not supported, it must be {} to {}",
+                    millis(respawn.countdown),
+                    COUNTDOWN_TIMES.start(),
+                    COUNTDOWN_TIMES.end()
+                ));
+            }
+            if !INVULNERABLE_TIMES.contains(&millis(respawn.invulnerable)) {
+                return Err(format!(
+                    "{} ms of invulnerability is not supported, it must be {} to {}",
+                    millis(respawn.invulnerable),
+                    INVULNERABLE_TIMES.start(),
+                    INVULNERABLE_TIMES.end()
+                ));
+            }
+        }
+        match &self.level {
+            Some(level) => level.validate(self.width, self.height, self.board().start()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for SnakeGameConfig {
+    fn default() -> Self {
+        SnakeGameConfig {
+            width: game_engine::GAME_AREA_WIDTH,
+            height: game_engine::GAME_AREA_HEIGHT,
+            seed: 0,
+            difficulty: Difficulty::default(),
+            wrap: false,
+            level: None,
+            scores_file: None,
+            respawn: None,
+            tick_rate: game_engine::DEFAULT_TICK_RATE,
+        }
+    }
+}
+
+/// A countdown before the snake moves, at the start of a game or after it lost a life.
+struct Countdown {
+    /// Goes off when the count is over. `None` once it went off and the snake waits for a
+    /// direction.
+    timer: Option<TimerId>,
+    /// The numbers counting down.
+    numbers: Entity,
+    /// Whether the snake lost a life, so it waits for a direction and then can't die for
+    /// a while.
+    respawn: bool,
+    /// Whether a direction was pressed during the count.
+    pressed: bool,
+}
+
+/// Ids of the scene's entities and objects in its world.
+struct SceneObjects {
+    snake: Entity,
+    food: Entity,
+    bonus: ObjectId,
+}
+
+pub struct SnakeGameScene {
+    config: SnakeGameConfig,
+    board: Arc<Board>,
+    seed: u64,
+    /// Set once the scene is loaded.
+    objects: Option<SceneObjects>,
+    games_over: u32,
+    /// Lives the snake had lost when the scene last checked.
+    deaths: u32,
+    score: Score,
+    /// How the last game scored, while the HUD shows it, and the timer that hides it.
+    game_over: Option<(ScoreBreakdown, TimerId)>,
+    /// Goes off every `MAGNET_PULL_INTERVAL` while the snake has a magnet. Set once the
+    /// scene is loaded.
+    magnet: Option<TimerId>,
+    /// Set while the snake holds still for a countdown.
+    countdown: Option<Countdown>,
+}
+
+impl SnakeGameScene {
+    pub fn new() -> SnakeGameScene {
+        SnakeGameScene::with_seed(rand::random())
+    }
+
+    /// Creates a scene whose random events are fully determined by `seed`.
+    pub fn with_seed(seed: u64) -> SnakeGameScene {
+        SnakeGameScene::with_config(SnakeGameConfig {
+            seed,
+            ..SnakeGameConfig::default()
+        })
+    }
+
+    pub fn with_config(config: SnakeGameConfig) -> SnakeGameScene {
+        SnakeGameScene {
+            board: Arc::new(config.board()),
+            seed: config.seed,
+            config,
+            objects: None,
+            games_over: 0,
+            deaths: 0,
+            score: Score::default(),
+            game_over: None,
+            magnet: None,
+            countdown: None,
+        }
+    }
+
+    pub fn config(&self) -> &SnakeGameConfig {
+        &self.config
+    }
+
+    pub fn board(&self) -> Arc<Board> {
+        Arc::clone(&self.board)
+    }
+
+    fn objects(&self) -> &SceneObjects {
+        self.objects.as_ref().expect("The scene isn't loaded")
+    }
+
+    pub fn snake<'a>(&self, world: &'a SnakeWorld) -> &'a Snake {
+        let components = world.components();
+        components.get(self.objects().snake).expect("No snake")
+    }
+
+    pub fn food<'a>(&self, world: &'a SnakeWorld) -> &'a Food {
+        world
+            .components()
+            .get(self.objects().food)
+            .expect("No food")
+    }
+
+    pub fn food_position(&self, world: &SnakeWorld) -> Position {
+        let components = world.components();
+        *components.get(self.objects().food).expect("No food")
+    }
+
+    fn bonus<'a>(&self, world: &'a SnakeWorld) -> &'a BonusFood {
+        world.get(self.objects().bonus).expect("No bonus")
+    }
+
+    /// Score of the game being played.
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Starts a new game, placing food from a fresh `seed`.
+    pub fn reset(&mut self, world: &mut SnakeWorld, seed: u64) {
+        self.seed = seed;
+        let objects = self.objects();
+        Food::reseed(world, objects.food, Arc::clone(&self.board), seed);
+        if let Some(bonus) = world.get_mut::<BonusFood>(objects.bonus) {
+            *bonus = BonusFood::with_seed(Arc::clone(&self.board), Self::bonus_seed(seed));
+        }
+        world.publish(SnakeGameSceneActions::Restart);
+        world.dispatch_events(self);
+    }
+
+    /// The bonus draws from its own generator so it doesn't change where food goes.
+    fn bonus_seed(seed: u64) -> u64 {
+        seed ^ 0x9e37_79b9_7f4a_7c15
+    }
+
+    /// Feeds the snake the food under its head, then moves the food.
+    fn eat_food(&mut self, world: &mut SnakeWorld) {
+        let kind = self.food(world).kind();
+        let speed = self.snake(world).current_speed();
+        let score = self.score.total();
+        self.score.record(ScoreEvent::Ate { kind, speed });
+        let position = self.food_position(world);
+        animations::flash(world, position, self.score.total() - score);
+        world.publish(SnakeGameSceneActions::EatApple);
+        if let Some(effect) = kind.effect() {
+            world.publish(effect);
+        }
+    }
+
+    /// Gives the snake what the bonus is worth now and takes it off the board.
+    fn eat_bonus(&mut self, world: &mut SnakeWorld) {
+        let apples = self.bonus(world).apples();
+        let speed = self.snake(world).current_speed();
+        self.score.record(ScoreEvent::Bonus { apples, speed });
+        world.publish(SnakeGameSceneActions::BonusApples(apples));
+        world.publish(SnakeGameSceneActions::EatBonus);
+    }
+
+    /// Takes points off for lives lost since the last update and, when the game is over,
+    /// shows its breakdown in the HUD for a while and starts a new score.
+    fn update_score(&mut self, world: &mut SnakeWorld, interval: Duration) {
+        let snake = self.snake(world);
+        let (deaths, games_over) = (snake.deaths(), snake.games_over());
+        self.score.tick(interval);
+        for _ in self.deaths..deaths {
+            self.score.record(ScoreEvent::LostLife);
+        }
+        self.deaths = deaths;
+        if games_over != self.games_over {
+            self.hide_game_over(world);
+            let timer = world.after(GAME_OVER_DISPLAY, SnakeGameSceneActions::HideGameOver);
+            self.game_over = Some((self.score.breakdown(), timer));
+            self.score.reset();
+        }
+    }
+
+    /// Sets the magnet's timer back to a whole `MAGNET_PULL_INTERVAL`, paused until the
+    /// snake has a magnet, so a new game doesn't inherit the last one's progress.
+    fn restart_magnet(&mut self, world: &mut SnakeWorld) {
+        if let Some(timer) = self.magnet.take() {
+            world.cancel_timer(timer);
+        }
+        let magnet = world.every(MAGNET_PULL_INTERVAL, SnakeGameSceneActions::MagnetPull);
+        world.pause_timer(magnet);
+        self.magnet = Some(magnet);
+    }
+
+    /// Holds the snake still while the board counts down to a new game, or to the snake
+    /// coming back after losing a life if `respawn`, if the scene counts down at all.
+    fn start_countdown(&mut self, world: &mut SnakeWorld, respawn: bool) {
+        let Some(config) = self.config.respawn else {
+            return;
+        };
+        self.stop_countdown(world);
+        Snake::hold(world, self.objects().snake, true);
+        let numbers = animations::countdown(world, &self.board, config.countdown, !respawn);
+        self.countdown = Some(Countdown {
+            timer: Some(world.after(config.countdown, SnakeGameSceneActions::Go)),
+            numbers,
+            respawn,
+            pressed: false,
+        });
+    }
+
+    fn stop_countdown(&mut self, world: &mut SnakeWorld) {
+        if let Some(countdown) = self.countdown.take() {
+            if let Some(timer) = countdown.timer {
+                world.cancel_timer(timer);
+            }
+            world.despawn(countdown.numbers);
+        }
+    }
+
+    /// Lets the snake go once the count is over, unless it came back from a lost life and
+    /// no direction was pressed yet.
+    fn countdown_over(&mut self, world: &mut SnakeWorld) {
+        let Some(countdown) = &mut self.countdown else {
+            return;
+        };
+        countdown.timer = None;
+        match countdown.respawn && !countdown.pressed {
+            true => animations::wait_for_direction(world, countdown.numbers),
+            false => self.release(world),
+        }
+    }
+
+    /// Counts a direction towards letting a snake that came back from a lost life go.
+    fn direction_pressed(&mut self, world: &mut SnakeWorld) {
+        match &mut self.countdown {
+            Some(countdown) if countdown.timer.is_some() => countdown.pressed = true,
+            Some(countdown) if countdown.respawn => self.release(world),
+            _ => (),
+        }
+    }
+
+    /// Lets the snake go, and makes it invulnerable for a while if it lost a life.
+    fn release(&mut self, world: &mut SnakeWorld) {
+        let Some(countdown) = self.countdown.take() else {
+            return;
+        };
+        let snake = self.objects().snake;
+        Snake::hold(world, snake, false);
+        if let (true, Some(config)) = (countdown.respawn, self.config.respawn) {
+            world.despawn(countdown.numbers);
+            Snake::protect(world, snake, config.invulnerable);
+        }
+    }
+
+    fn hide_game_over(&mut self, world: &mut SnakeWorld) {
+        if let Some((_, timer)) = self.game_over.take() {
+            world.cancel_timer(timer);
+        }
+    }
+
+    /// HUD lines with how the last game scored.
+    fn draw_game_over(&self, breakdown: &ScoreBreakdown) -> Vec<String> {
+        vec![
+            format!("GAME OVER   Best combo x{}", breakdown.best_combo),
+            format!(
+                "Score  :{:>6}   Food :{:>+6}",
+                breakdown.total(),
+                breakdown.food
+            ),
+            format!(
+                "Speed {:+}   Combo {:+}   Lives {:+}",
+                breakdown.speed,
+                breakdown.combo,
+                -i64::from(breakdown.penalty)
+            ),
+            String::from("[R] - Restart Game  /  Arrow keys - Change snake direction"),
+        ]
+    }
+
+    /// Adds a finished game that scored `score` to the high-score table, if the scene keeps
+    /// one and the snake ate anything.
+    fn save_score(&self, score: u32, apples: u16) {
+        let path = match &self.config.scores_file {
+            Some(path) if apples > 0 => path,
+            _ => return,
+        };
+        let entry = ScoreEntry::new(score, apples, &self.config);
+        // A missing or unwritable table shouldn't end the game
+        let _ = HighScores::load(path).and_then(|mut scores| {
+            scores.add(entry);
+            scores.save(path)
+        });
+    }
+}
+
+impl Default for SnakeGameScene {
+    fn default() -> Self {
+        SnakeGameScene::new()
+    }
+}
+
+impl GameScene<SnakeGameSceneActions> for SnakeGameScene {
+    fn load(&mut self, world: &mut SnakeWorld) {
+        let board = &self.board;
+        let bonus = BonusFood::with_seed(Arc::clone(board), Self::bonus_seed(self.seed));
+        world.add_object(Box::new(Walls::new(Arc::clone(board))));
+        let (speed, lives) = (
+            self.config.difficulty.speed(),
+            self.config.difficulty.lives(),
+        );
+        let objects = SceneObjects {
+            snake: Snake::spawn(world, Arc::clone(board), speed, lives),
+            food: Food::spawn(world, Arc::clone(board), self.seed),
+            bonus: world.add_object(Box::new(bonus)),
+        };
+        for hazard in self.config.level.iter().flat_map(|level| &level.hazards) {
+            world.add_object(hazards::create(Arc::clone(board), hazard));
+        }
+        for layer in [WALLS_LAYER, FOOD_LAYER, BONUS_LAYER, HAZARD_LAYER] {
+            world.add_collision_rule(SNAKE_LAYER, layer);
+        }
+        world.add_system(Box::new(SnakeEffects));
+        world.add_system(Box::new(Movement));
+        world.add_system(Box::new(SnakeSystem));
+        world.add_system(Box::new(FoodSystem));
+        world.add_system(Box::new(Lifetimes));
+        self.objects = Some(objects);
+        self.restart_magnet(world);
+        self.start_countdown(world, false);
+    }
+    fn update(&mut self, world: &mut SnakeWorld, interval: Duration) {
+        let snake = self.snake(world);
+        let (games_over, apples) = (snake.games_over(), snake.last_game_apples());
+        let snake_position = snake.head(world);
+        let (ghost, magnet, invulnerable) = (
+            snake.has_effect(Effect::Ghost),
+            snake.has_effect(Effect::Magnet),
+            snake.invulnerable(),
+        );
+        let deaths = self.deaths;
+        self.update_score(world, interval);
+        if games_over != self.games_over {
+            self.games_over = games_over;
+            let score = self
+                .game_over
+                .as_ref()
+                .map_or(0, |(breakdown, _)| breakdown.total());
+            self.save_score(score, apples);
+            self.start_countdown(world, false);
+        } else if self.deaths != deaths {
+            self.start_countdown(world, true);
+        }
+        if let Some(timer) = self.magnet {
+            match magnet {
+                true => world.resume_timer(timer),
+                false => world.pause_timer(timer),
+            }
+        }
+        world.publish(SnakeGameSceneActions::Chase(snake_position));
+        let hit = |layer: &str| {
+            world.collisions().iter().any(|collision| {
+                let layers = (collision.mover_layer, collision.other_layer);
+                layers == (SNAKE_LAYER, layer) || layers == (layer, SNAKE_LAYER)
+            })
+        };
+        // A ghost, or an invulnerable snake, goes through walls but still dies off the edge
+        // of the board
+        let off_board = !self.board.contains(self.board.cell(snake_position));
+        if off_board || (hit(WALLS_LAYER) && !ghost && !invulnerable) {
+            world.publish(SnakeGameSceneActions::HitWall);
+        } else if hit(HAZARD_LAYER) && !invulnerable {
+            world.publish(SnakeGameSceneActions::HitHazard);
+        } else if hit(FOOD_LAYER) {
+            self.eat_food(world);
+        } else if hit(BONUS_LAYER) {
+            self.eat_bonus(world);
+        }
+    }
+    fn draw_hud(&self, world: &SnakeWorld, _width: usize, _height: usize) -> Vec<String> {
+        if let Some((breakdown, _)) = &self.game_over {
+            return self.draw_game_over(breakdown);
+        }
+        let snake = self.snake(world);
+        let speed = format!("Speed  :{:>6} blocks/second", snake.current_speed());
+        let controls = String::from("[R] - Restart Game  /  Arrow keys - Change snake direction");
+        let mut lives = format!("Lives  :{:>6}", snake.lives());
+        if let Some(remaining) = snake.invulnerable_for() {
+            lives.push_str(&format!(
+                "   Safe {}s",
+                remaining.as_millis().div_ceil(1000)
+            ));
+        }
+        for (effect, remaining) in snake.effects().active() {
+            lives.push_str(&format!(
+                "   {} {}s",
+                effect.name(),
+                remaining.as_millis().div_ceil(1000)
+            ));
+        }
+        let mut apples = format!(
+            "Apples :{:>6}   Score :{:>6}",
+            snake.apples(),
+            self.score.total()
+        );
+        if self.score.combo() > 1 {
+            apples.push_str(&format!(" x{}", self.score.combo()));
+        }
+        let bonus = self.bonus(world);
+        if let Some(remaining) = bonus.remaining() {
+            apples.push_str(&format!(
+                "   Bonus :{:>3} for {}s",
+                bonus.apples(),
+                remaining.as_millis().div_ceil(1000)
+            ));
+        }
+        let resp = vec![speed, lives, apples, controls];
+        resp
+    }
+    fn draw_title(&self, width: usize, _height: usize) -> String {
+        let mut s = String::new();
+        s.push_str(&" ".repeat((width - 13) / 2));
+        s.push_str("*** SNAKE ***");
+        s.push_str(&" ".repeat((width - 13) / 2));
+        s
+    }
+    fn input(&mut self, world: &mut SnakeWorld, game_data: GameData) {
+        match game_data.action {
+            Action::Restart => world.publish(SnakeGameSceneActions::Restart),
+            Action::MoveDown | Action::MoveUp | Action::MoveLeft | Action::MoveRight => {
+                Snake::turn(world, self.objects().snake, game_data.action);
+                self.direction_pressed(world);
+            }
+            Action::Quit => self.save_score(self.score.total(), self.snake(world).apples()),
+            Action::Command(_) | Action::ToggleDebug => (),
+        }
+    }
+    fn subscribes(&self, event: &SnakeGameSceneActions) -> bool {
+        matches!(
+            event,
+            SnakeGameSceneActions::Restart
+                | SnakeGameSceneActions::MagnetPull
+                | SnakeGameSceneActions::HideGameOver
+                | SnakeGameSceneActions::Go
+        )
+    }
+    /// The snake got a restart first, so its deaths are already counted.
+    fn on_event(&mut self, world: &mut SnakeWorld, event: SnakeGameSceneActions) {
+        match event {
+            SnakeGameSceneActions::Restart => {
+                self.score.reset();
+                self.hide_game_over(world);
+                self.restart_magnet(world);
+                self.deaths = self.snake(world).deaths();
+                self.start_countdown(world, false);
+            }
+            SnakeGameSceneActions::MagnetPull => {
+                let head = self.snake(world).head(world);
+                world.publish(SnakeGameSceneActions::Pull(head));
+            }
+            SnakeGameSceneActions::HideGameOver => self.game_over = None,
+            SnakeGameSceneActions::Go => self.countdown_over(world),
+            _ => (),
+        }
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(difficulty: Difficulty, tick_rate: usize) -> SnakeGameConfig {
+        SnakeGameConfig {
+            difficulty,
+            tick_rate,
+            ..SnakeGameConfig::default()
+        }
+    }
+
+    #[test]
+    fn tick_rate_keeps_up_with_the_snake() {
+        assert!(config(Difficulty::Easy, 10).validate().is_ok());
+        assert!(config(Difficulty::Normal, 15).validate().is_ok());
+        assert!(config(Difficulty::Hard, 22).validate().is_ok());
+        assert_eq!(
+            config(Difficulty::Hard, 21).validate(),
+            Err(String::from(
+                "a tick rate of 21 is too slow for hard difficulty, it must be at least 22"
+            ))
+        );
+        assert!(config(Difficulty::Normal, 14).validate().is_err());
+    }
+}
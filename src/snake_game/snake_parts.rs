@@ -0,0 +1,16 @@
+use crate::game_utils::Direction;
+
+/// A part of the snake's body behind the head: the component with the direction it last
+/// moved in. Its cell is its `Position`.
+#[derive(Copy, Clone)]
+pub struct SnakeBody {
+    pub direction: Direction,
+}
+
+impl Default for SnakeBody {
+    fn default() -> Self {
+        SnakeBody {
+            direction: Direction::right(),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::style::Theme;
+
+/// Parts of the game a theme can style.
+pub const PARTS: [&str; 21] = [
+    "title",
+    "hud",
+    "border",
+    "snake_head",
+    "snake_body",
+    "apple",
+    "golden_apple",
+    "berry",
+    "pepper",
+    "heart",
+    "bonus",
+    "shield",
+    "ghost",
+    "snail",
+    "magnet",
+    "ball",
+    "patrol",
+    "chaser",
+    "walls",
+    "flash",
+    "countdown",
+];
+
+/// Themes shipped with the game, by the name the settings use.
+pub const BUILTIN: [(&str, &str); 3] = [
+    ("classic", include_str!("../../themes/classic.toml")),
+    ("forest", include_str!("../../themes/forest.toml")),
+    ("ocean", include_str!("../../themes/ocean.toml")),
+];
+
+/// The built-in theme called `name`.
+pub fn builtin(name: &str) -> Option<Theme> {
+    BUILTIN
+        .iter()
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, text)| parse(text).unwrap())
+}
+
+/// Reads a theme file for the snake game's parts.
+pub fn parse(text: &str) -> Result<Theme, String> {
+    Theme::parse(text, &PARTS)
+}
+
+pub fn load(path: &str) -> Result<Theme, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    parse(&text).map_err(|e| format!("{}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::{Color, Style};
+
+    #[test]
+    fn builtin_themes_parse() {
+        for (name, _) in BUILTIN {
+            assert!(builtin(name).is_some(), "{}", name);
+        }
+        assert!(builtin("missing").is_none());
+    }
+
+    #[test]
+    fn themes_style_the_parts_they_set() {
+        let theme =
+            parse("name = \"Test\"\n[apple]\nfg = \"bright-red\"\nbg = \"#102030\"\nbold = true\n")
+                .unwrap();
+        assert_eq!(theme.name, "Test");
+        let apple = Style {
+            fg: Color::Ansi(9),
+            bg: Color::Rgb(0x10, 0x20, 0x30),
+            bold: true,
+        };
+        assert_eq!(theme.style("apple"), apple);
+        assert_eq!(theme.style("walls"), Style::default());
+    }
+
+    #[test]
+    fn bad_themes_say_what_is_wrong() {
+        let error = |text: &str| parse(text).unwrap_err();
+        assert_eq!(error("name = 3"), "`name`: expected a string, found 3");
+        assert!(error("[cat]\nfg = \"red\"")
+            .starts_with("unknown part `cat`, expected one of: title, hud"));
+        assert_eq!(
+            error("apple = \"red\""),
+            "`apple`: expected a table, found \"red\""
+        );
+        assert_eq!(
+            error("[apple]\nfg = \"pink\""),
+            "`apple.fg`: unknown color `pink`, expected `default`, `#rrggbb` or one of: black, red, green, yellow, blue, magenta, cyan, white"
+        );
+        assert_eq!(
+            error("[apple]\nbg = \"#12345\""),
+            "`apple.bg`: invalid color `#12345`, expected `#rrggbb`"
+        );
+        assert_eq!(
+            error("[apple]\nfg = 1"),
+            "`apple.fg`: expected a color, found 1"
+        );
+        assert_eq!(
+            error("[apple]\nbold = \"yes\""),
+            "`apple.bold`: expected true or false, found \"yes\""
+        );
+        assert_eq!(
+            error("[apple]\nitalic = true"),
+            "`apple.italic`: unknown key, expected fg, bg or bold"
+        );
+        assert!(error("[apple]\n\nfg = ").starts_with("line 3: "));
+    }
+}
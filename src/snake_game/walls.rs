@@ -0,0 +1,57 @@
+use crate::collision::Collider;
+use crate::game_engine::{Action, Draw, DrawLayer, Game, GameObject, GameScreen, Update};
+use crate::game_utils::Position;
+use crate::snake_game::board::Board;
+use crate::snake_game::snake_game_scene::{SnakeGameSceneActions, WALLS_LAYER};
+use std::any::Any;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The walls of the board's level, drawn and collided with as one object.
+pub struct Walls {
+    board: Arc<Board>,
+}
+
+impl Walls {
+    pub fn new(board: Arc<Board>) -> Walls {
+        Walls { board }
+    }
+}
+
+impl Draw for Walls {
+    fn draw(&self, screen: &mut GameScreen) {
+        let (glyph, style) = (screen.glyph("wall"), screen.style("walls"));
+        for (x, y) in self.board.walls() {
+            Game::<SnakeGameSceneActions>::draw_styled_point(screen, &glyph, style, *x, *y);
+        }
+    }
+    fn get_position(&self) -> Position {
+        Position { x: 0.0, y: 0.0 }
+    }
+    fn layer(&self) -> DrawLayer {
+        DrawLayer::Background
+    }
+}
+
+impl Update for Walls {
+    fn update(&mut self, _time_since_last_call: Duration) {}
+}
+
+impl GameObject for Walls {
+    type Item = SnakeGameSceneActions;
+
+    fn action(&mut self, _action: Action) {}
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn collider(&self) -> Option<Collider> {
+        Some(Collider {
+            layer: WALLS_LAYER,
+            cells: self.board.walls().copied().collect(),
+            moving: false,
+        })
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+
+/// A terminal color: the terminal's own, one of the 16 ANSI colors (`8` to `15` are the
+/// bright ones) or an RGB value.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Color {
+    #[default]
+    Default,
+    Ansi(u8),
+    Rgb(u8, u8, u8),
+}
+
+const COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// The xterm values of the 16 ANSI colors, used to pick the closest one to an RGB color.
+const ANSI_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+impl Color {
+    /// SGR parameters that set this color as the foreground, or as the background if
+    /// `background`, in `mode`. Empty if the color can't be shown.
+    fn sgr(&self, background: bool, mode: ColorMode) -> String {
+        let base = if background { 40 } else { 30 };
+        let color = match (self, mode) {
+            (Color::Default, _) | (_, ColorMode::Plain) => return String::new(),
+            (Color::Rgb(r, g, b), ColorMode::Ansi16) => Color::Ansi(nearest_ansi(*r, *g, *b)),
+            (color, _) => *color,
+        };
+        match (color, mode) {
+            (Color::Ansi(n), _) if n < 8 => format!("{}", base + n),
+            (Color::Ansi(n), _) => format!("{}", base + 60 + n % 8),
+            (Color::Rgb(r, g, b), ColorMode::Ansi256) => {
+                let level = |c: u8| (c as u16 * 5 + 127) / 255;
+                let index = 16 + 36 * level(r) + 6 * level(g) + level(b);
+                format!("{};5;{}", base + 8, index)
+            }
+            (Color::Rgb(r, g, b), _) => format!("{};2;{};{};{}", base + 8, r, g, b),
+            (Color::Default, _) => String::new(),
+        }
+    }
+}
+
+fn nearest_ansi(r: u8, g: u8, b: u8) -> u8 {
+    let distance = |(pr, pg, pb): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, pr) + d(g, pg) + d(b, pb)
+    };
+    (0..16u8)
+        .min_by_key(|n| distance(ANSI_PALETTE[*n as usize]))
+        .unwrap()
+}
+
+impl std::str::FromStr for Color {
+    type Err = String;
+
+    /// Reads `default`, a color name such as `red` or `bright-red`, or `#rrggbb`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "default" {
+            return Ok(Color::Default);
+        }
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb(r, g, b)),
+                _ => Err(format!("invalid color `{}`, expected `#rrggbb`", s)),
+            };
+        }
+        let (bright, name) = match s.strip_prefix("bright-") {
+            Some(name) => (8, name),
+            None => (0, s),
+        };
+        match COLOR_NAMES.iter().position(|color| *color == name) {
+            Some(n) => Ok(Color::Ansi(n as u8 + bright)),
+            None => Err(format!(
+                "unknown color `{}`, expected `default`, `#rrggbb` or one of: {}",
+                s,
+                COLOR_NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+/// How text is drawn: its colors and weight.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Style {
+    pub fg: Color,
+    pub bg: Color,
+    pub bold: bool,
+}
+
+impl Style {
+    /// `text` wrapped in the escape codes that draw it in this style, as far as `mode` allows.
+    pub fn paint(&self, text: &str, mode: ColorMode) -> String {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push(String::from("1"));
+        }
+        codes.push(self.fg.sgr(false, mode));
+        codes.push(self.bg.sgr(true, mode));
+        codes.retain(|code| !code.is_empty());
+        if codes.is_empty() {
+            return text.to_string();
+        }
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+    }
+}
+
+/// The colors a terminal can show.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ColorMode {
+    /// No colors, only bold text. Used when `NO_COLOR` is set.
+    Plain,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorMode {
+    /// Guesses the terminal's colors from `NO_COLOR`, `COLORTERM` and `TERM`.
+    pub fn detect() -> ColorMode {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        let term = var("TERM");
+        if !var("NO_COLOR").is_empty() || term == "dumb" {
+            ColorMode::Plain
+        } else if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+            ColorMode::TrueColor
+        } else if term.contains("256color") {
+            ColorMode::Ansi256
+        } else {
+            ColorMode::Ansi16
+        }
+    }
+}
+
+/// Styles for the parts of a game, by name. Parts a theme doesn't set are drawn unstyled.
+///
+/// A theme file is TOML with a table for each part it sets:
+///
+/// ```toml
+/// name = "Forest"
+///
+/// [snake_head]
+/// fg = "bright-green"
+/// bold = true
+///
+/// [apple]
+/// fg = "#e03030"
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Theme {
+    pub name: String,
+    styles: HashMap<String, Style>,
+}
+
+impl Theme {
+    /// Reads a theme file that may only set styles for `parts`.
+    pub fn parse(text: &str, parts: &[&str]) -> Result<Theme, String> {
+        let table: toml::Table = text.parse().map_err(|e: toml::de::Error| {
+            let line = e
+                .span()
+                .map_or(1, |span| text[..span.start].matches('\n').count() + 1);
+            format!(
+                "line {}: {}",
+                line,
+                e.message().trim_end().replace('\n', ", ")
+            )
+        })?;
+        let mut theme = Theme::default();
+        for (key, value) in &table {
+            match (key.as_str(), value) {
+                ("name", toml::Value::String(name)) => theme.name = name.clone(),
+                ("name", value) => {
+                    return Err(format!("`name`: expected a string, found {}", value))
+                }
+                (part, toml::Value::Table(values)) if parts.contains(&part) => {
+                    let style = Self::parse_style(part, values)?;
+                    theme.styles.insert(part.to_string(), style);
+                }
+                (part, value) if parts.contains(&part) => {
+                    return Err(format!("`{}`: expected a table, found {}", part, value))
+                }
+                (part, _) => {
+                    return Err(format!(
+                        "unknown part `{}`, expected one of: {}",
+                        part,
+                        parts.join(", ")
+                    ))
+                }
+            }
+        }
+        Ok(theme)
+    }
+
+    fn parse_style(part: &str, values: &toml::Table) -> Result<Style, String> {
+        let mut style = Style::default();
+        for (key, value) in values {
+            let error = |message: String| format!("`{}.{}`: {}", part, key, message);
+            match (key.as_str(), value) {
+                ("fg", toml::Value::String(color)) => style.fg = color.parse().map_err(error)?,
+                ("bg", toml::Value::String(color)) => style.bg = color.parse().map_err(error)?,
+                ("bold", toml::Value::Boolean(bold)) => style.bold = *bold,
+                ("fg" | "bg", value) => {
+                    return Err(error(format!("expected a color, found {}", value)))
+                }
+                ("bold", value) => {
+                    return Err(error(format!("expected true or false, found {}", value)))
+                }
+                _ => return Err(error(String::from("unknown key, expected fg, bg or bold"))),
+            }
+        }
+        Ok(style)
+    }
+
+    pub fn style(&self, part: &str) -> Style {
+        self.styles.get(part).copied().unwrap_or_default()
+    }
+}
+
+/// Characters drawn for the parts of a game, by name, and the columns a board cell takes to
+/// fit them.
+#[derive(Clone, Debug)]
+pub struct GlyphSet {
+    pub name: String,
+    cell_width: usize,
+    glyphs: HashMap<String, String>,
+}
+
+impl GlyphSet {
+    pub fn new(name: &str, cell_width: usize, glyphs: &[(&str, &str)]) -> GlyphSet {
+        GlyphSet {
+            name: name.to_string(),
+            cell_width,
+            glyphs: glyphs
+                .iter()
+                .map(|(part, glyph)| (part.to_string(), glyph.to_string()))
+                .collect(),
+        }
+    }
+
+    pub fn cell_width(&self) -> usize {
+        self.cell_width
+    }
+
+    /// The glyph for `part`, or `?` if the set has none.
+    pub fn glyph(&self, part: &str) -> &str {
+        self.glyphs.get(part).map_or("?", String::as_str)
+    }
+
+    /// Every part the set has a glyph for, with the glyph.
+    pub fn glyphs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.glyphs
+            .iter()
+            .map(|(part, glyph)| (part.as_str(), glyph.as_str()))
+    }
+}
+
+impl Default for GlyphSet {
+    fn default() -> Self {
+        GlyphSet::new("", 1, &[])
+    }
+}
+
+/// Columns `text` takes in a terminal. Emoji and East Asian wide characters take two,
+/// combining marks and joiners none.
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// Columns `c` takes, as in `display_width`. Symbols drawn as emoji by default, like
+/// `✨` or `⭐`, are wide even below the emoji blocks.
+pub fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x0300..=0x036f | 0x200b..=0x200f | 0xfe00..=0xfe0f => 0,
+        0x1100..=0x115f
+        | 0x231a..=0x231b
+        | 0x23e9..=0x23ec
+        | 0x23f0
+        | 0x23f3
+        | 0x25fd..=0x25fe
+        | 0x2614..=0x2615
+        | 0x2648..=0x2653
+        | 0x267f
+        | 0x2693
+        | 0x26a1
+        | 0x26aa..=0x26ab
+        | 0x26bd..=0x26be
+        | 0x26c4..=0x26c5
+        | 0x26ce
+        | 0x26d4
+        | 0x26ea
+        | 0x26f2..=0x26f3
+        | 0x26f5
+        | 0x26fa
+        | 0x26fd
+        | 0x2705
+        | 0x270a..=0x270b
+        | 0x2728
+        | 0x274c
+        | 0x274e
+        | 0x2753..=0x2755
+        | 0x2757
+        | 0x2795..=0x2797
+        | 0x27b0
+        | 0x27bf
+        | 0x2b1b..=0x2b1c
+        | 0x2b50
+        | 0x2b55
+        | 0x2e80..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f004
+        | 0x1f0cf
+        | 0x1f18e
+        | 0x1f191..=0x1f19a
+        | 0x1f200..=0x1f251
+        | 0x1f300..=0x1f64f
+        | 0x1f680..=0x1f6ff
+        | 0x1f7e0..=0x1f7eb
+        | 0x1f900..=0x1f9ff
+        | 0x1fa70..=0x1faff
+        | 0x20000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn char_width_counts_columns() {
+        for c in ['a', '#', '█', '━', '✦', '✺', '♥', '☠'] {
+            assert_eq!(char_width(c), 1, "{}", c);
+        }
+        for c in [
+            '✨', '✅', '❌', '⭐', '⚡', '⌛', '🍎', '🧲', '🟢', '🫐', '中', '한',
+        ] {
+            assert_eq!(char_width(c), 2, "{}", c);
+        }
+        for c in ['\u{301}', '\u{200d}', '\u{fe0f}'] {
+            assert_eq!(char_width(c), 0, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn display_width_adds_up_the_characters() {
+        assert_eq!(display_width(""), 0);
+        assert_eq!(display_width("score 12"), 8);
+        assert_eq!(display_width("✨ x2"), 5);
+        assert_eq!(display_width("🍎🍇"), 4);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("👩\u{200d}💻"), 4);
+    }
+}
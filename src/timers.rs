@@ -0,0 +1,194 @@
+use std::time::Duration;
+
+/// Identifies a timer until it goes off for the last time or is cancelled. Ids aren't
+/// reused.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TimerId(u64);
+
+struct Timer<A> {
+    id: TimerId,
+    event: A,
+    /// Time until it goes off next.
+    remaining: Duration,
+    /// Time between two times it goes off, for a repeating timer.
+    period: Option<Duration>,
+    paused: bool,
+    /// Whether it counts the next tick. A timer set during a tick starts with the next one.
+    counting: bool,
+}
+
+/// Timers that send an event once their time is up, counted in simulation time: they only
+/// move on with the ticks of the game, so a replayed or headless game sees them go off on
+/// the same ticks, and they stand still while the game is paused. `World` sets them.
+pub(crate) struct Timers<A> {
+    timers: Vec<Timer<A>>,
+    next_id: u64,
+}
+
+impl<A> Timers<A> {
+    pub fn new() -> Timers<A> {
+        Timers {
+            timers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Sends `event` once, after `delay`. A timer that isn't `counting` yet starts with the
+    /// tick after the next `advance`.
+    pub fn after(&mut self, delay: Duration, event: A, counting: bool) -> TimerId {
+        self.add(delay, None, event, counting)
+    }
+
+    /// Sends `event` every `period` until the timer is cancelled.
+    ///
+    /// Panics if `period` is zero.
+    pub fn every(&mut self, period: Duration, event: A, counting: bool) -> TimerId {
+        assert!(!period.is_zero(), "A repeating timer needs a period");
+        self.add(period, Some(period), event, counting)
+    }
+
+    fn add(
+        &mut self,
+        delay: Duration,
+        period: Option<Duration>,
+        event: A,
+        counting: bool,
+    ) -> TimerId {
+        self.next_id += 1;
+        let id = TimerId(self.next_id);
+        self.timers.push(Timer {
+            id,
+            event,
+            remaining: delay,
+            period,
+            paused: false,
+            counting,
+        });
+        id
+    }
+
+    /// Stops the timer for good. Returns whether it was still set.
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        let count = self.timers.len();
+        self.timers.retain(|timer| timer.id != id);
+        self.timers.len() != count
+    }
+
+    /// Stops the timer's time until it is resumed.
+    pub fn pause(&mut self, id: TimerId) {
+        if let Some(timer) = self.timer_mut(id) {
+            timer.paused = true;
+        }
+    }
+
+    pub fn resume(&mut self, id: TimerId) {
+        if let Some(timer) = self.timer_mut(id) {
+            timer.paused = false;
+        }
+    }
+
+    /// Time until the timer goes off next, or `None` if it isn't set.
+    pub fn remaining(&self, id: TimerId) -> Option<Duration> {
+        self.timers
+            .iter()
+            .find(|timer| timer.id == id)
+            .map(|timer| timer.remaining)
+    }
+
+    fn timer_mut(&mut self, id: TimerId) -> Option<&mut Timer<A>> {
+        self.timers.iter_mut().find(|timer| timer.id == id)
+    }
+}
+
+impl<A: Copy> Timers<A> {
+    /// Moves every running timer on by `interval` and returns the events of those that
+    /// went off, in the order they were set. A repeating timer goes off as many times as
+    /// its period fits.
+    pub fn advance(&mut self, interval: Duration) -> Vec<A> {
+        let mut events = Vec::new();
+        self.timers.retain_mut(|timer| {
+            if !timer.counting {
+                timer.counting = true;
+                return true;
+            }
+            if timer.paused {
+                return true;
+            }
+            let mut elapsed = interval;
+            while elapsed >= timer.remaining {
+                elapsed -= timer.remaining;
+                events.push(timer.event);
+                match timer.period {
+                    Some(period) => timer.remaining = period,
+                    None => return false,
+                }
+            }
+            timer.remaining -= elapsed;
+            true
+        });
+        events
+    }
+}
+
+impl<A> Default for Timers<A> {
+    fn default() -> Self {
+        Timers::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn go_off_in_the_order_they_were_set() {
+        let mut timers = Timers::new();
+        timers.after(ms(30), "late", true);
+        timers.after(ms(10), "soon", true);
+        timers.every(ms(20), "every", true);
+        assert_eq!(timers.advance(ms(10)), ["soon"]);
+        assert_eq!(timers.advance(ms(10)), ["every"]);
+        assert_eq!(timers.advance(ms(30)), ["late", "every"]);
+        assert_eq!(timers.advance(ms(40)), ["every", "every"]);
+    }
+
+    #[test]
+    fn paused_timers_keep_their_time() {
+        let mut timers = Timers::new();
+        let every = timers.every(ms(10), "every", true);
+        let once = timers.after(ms(15), "once", true);
+        assert_eq!(timers.advance(ms(25)), ["every", "every", "once"]);
+        assert_eq!(timers.remaining(every), Some(ms(5)));
+        assert_eq!(timers.remaining(once), None);
+        timers.pause(every);
+        assert!(timers.advance(ms(100)).is_empty());
+        assert_eq!(timers.remaining(every), Some(ms(5)));
+        timers.resume(every);
+        assert_eq!(timers.advance(ms(5)), ["every"]);
+        assert_eq!(timers.remaining(every), Some(ms(10)));
+    }
+
+    #[test]
+    fn cancelled_timers_never_go_off() {
+        let mut timers = Timers::new();
+        let once = timers.after(ms(10), "once", true);
+        let every = timers.every(ms(10), "every", true);
+        assert!(timers.cancel(once));
+        assert!(!timers.cancel(once));
+        assert_eq!(timers.advance(ms(10)), ["every"]);
+        assert!(timers.cancel(every));
+        assert!(timers.advance(ms(50)).is_empty());
+    }
+
+    #[test]
+    fn timers_set_during_a_tick_start_with_the_next() {
+        let mut timers = Timers::new();
+        timers.after(ms(10), "once", false);
+        assert!(timers.advance(ms(10)).is_empty());
+        assert_eq!(timers.advance(ms(10)), ["once"]);
+    }
+}